@@ -1,12 +1,14 @@
-use crate::data::{Assignment, Question};
-use crate::io::{FileOps, View};
+use crate::data::latex::LatexEngine;
+use crate::data::{roster, Assignment, BonusPolicy, Question, Scaling, Student};
+use crate::io::{storage, FileError, FileOps, LoadChoice, View};
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct App<V: View, F: FileOps> {
     assignment: Assignment,
-    student: String,
+    filename: String,
+    student: Student,
     student_idx: u32,
     question: Question,
     question_idx: u32,
@@ -18,13 +20,10 @@ impl<V: View, F: FileOps> App<V, F> {
     pub fn new() -> Self {
         Self {
             assignment: Assignment::new("default".to_string(), "none".to_string()),
-            student: "none".to_string(),
+            filename: "none.emark".to_string(),
+            student: Student::new("none"),
             student_idx: 0,
-            question: Question {
-                num: 1,
-                part: 1,
-                out_of: 0,
-            },
+            question: Question::new(1, 1, 0),
             question_idx: 0,
             view: PhantomData,
             file_ops: PhantomData,
@@ -44,14 +43,20 @@ impl<V: View, F: FileOps> App<V, F> {
         loop {
             let choice = V::show_menu(&header, &menu);
             match choice {
-                1 => {
-                    self.assignment = V::create_assignment();
-                    self.new_student();
+                1 => self.new_assignment(),
+                2 => {
+                    let loaded = match V::choose_assignment_file() {
+                        Some(LoadChoice::File(filename)) => self.load(filename),
+                        Some(LoadChoice::Backup(backup)) => {
+                            F::restore_backup(&backup).and_then(|f| self.load(f))
+                        }
+                        None => continue,
+                    };
+                    if let Err(e) = loaded {
+                        V::show_error(&e);
+                        continue;
+                    }
                 }
-                2 => match V::load_assignment() {
-                    Some(asn) => self.set_assignment(asn),
-                    None => continue,
-                },
                 _ => break,
             }
 
@@ -60,6 +65,31 @@ impl<V: View, F: FileOps> App<V, F> {
         }
     }
 
+    // Starts a new assignment, with its students from a roster or entered
+    // by hand, at its first student and question.
+    fn new_assignment(&mut self) {
+        self.assignment = V::create_assignment();
+        self.student_idx = 0;
+        self.question_idx = 0;
+        let format = V::choose_storage_format();
+        self.filename = storage::assignment_filename(&self.assignment, format);
+        if V::confirm("Import students from a roster?") {
+            self.import_roster();
+        }
+        if self.assignment.num_students() == 0 {
+            self.new_student();
+        } else {
+            self.student = self.assignment.get_student_at(self.student_idx);
+        }
+    }
+
+    fn load(&mut self, filename: String) -> Result<(), FileError> {
+        let asn = F::load_assignment(&filename)?;
+        self.filename = filename;
+        self.set_assignment(asn);
+        Ok(())
+    }
+
     fn set_assignment(&mut self, assignment: Assignment) {
         self.assignment = assignment;
         self.student_idx = 0;
@@ -71,38 +101,176 @@ impl<V: View, F: FileOps> App<V, F> {
     // Assignment menu ///////////////////////////////////////////////////////
     fn asn_menu(&mut self) {
         let menu = vec![
-            "Start Grading".to_string(),
+            "Grade By Student".to_string(),
+            "Grade By Question".to_string(),
             "New Student".to_string(),
+            "Import Roster".to_string(),
             "Next Student".to_string(),
             "Prev Student".to_string(),
+            "Go To Student".to_string(),
             "Display Current Grade Sheet".to_string(),
-            "Dump Grade Sheet To Latex".to_string(),
-            "Dump ALL To Latex".to_string(),
+            "Export Grade Sheets".to_string(),
+            "Export Gradebook CSV".to_string(),
+            "Export Comments CSV".to_string(),
+            "Export Grades For LMS".to_string(),
+            "Reports".to_string(),
+            "Manage Students".to_string(),
+            "Edit Structure".to_string(),
+            "Undo".to_string(),
+            "Redo".to_string(),
             "Back".to_string(),
         ];
 
         loop {
-            let header = format!("{} Menu ({})", self.assignment.title, self.student);
+            let header = format!(
+                "{} Menu ({}) | {}",
+                self.assignment.title,
+                self.student.name,
+                self.progress()
+            );
 
             let choice = V::show_menu(&header, &menu);
             match choice {
                 1 => self.question_menu(),
-                2 => self.new_student(),
-                3 => self.change_student(1),
-                4 => self.change_student(-1),
-                5 => V::show_grade_sheet(&self.assignment, &self.student),
-                6 => F::save_latex_grade_sheet(&self.assignment, &self.student),
-                7 => F::save_all_latex_grade_sheets(&self.assignment),
+                2 => self.grade_by_question_menu(),
+                3 => self.new_student(),
+                4 => self.import_roster(),
+                5 => self.change_student(1),
+                6 => self.change_student(-1),
+                7 => self.go_to_student(),
+                8 => V::show_grade_sheet(&self.assignment, self.student.id),
+                9 => self.export_grade_sheets(),
+                10 => Self::report(F::save_gradebook(&self.assignment, false)),
+                11 => Self::report(F::save_gradebook(&self.assignment, true)),
+                12 => self.export_lms_grades(),
+                13 => self.reports_menu(),
+                14 => self.student_menu(),
+                15 => self.structure_menu(),
+                16 => self.undo(),
+                17 => self.redo(),
                 _ => break,
             }
         }
     }
 
+    // The grading progress, overall and for each question part.
+    fn progress(&self) -> String {
+        let num_students = self.assignment.num_students();
+        let parts: Vec<String> = self
+            .assignment
+            .get_questions()
+            .iter()
+            .map(|q| {
+                let graded = self.assignment.num_graded(q);
+                format!("{} {}/{}", q.label(), graded, num_students)
+            })
+            .collect();
+        format!(
+            "Graded {}/{} ({})",
+            self.assignment.total_graded(),
+            num_students * self.assignment.num_questions(),
+            parts.join(", ")
+        )
+    }
+
+    // Grade sheets give full marks for parts that have not been graded, so
+    // check before writing them.
+    fn ready_to_export(&self) -> bool {
+        let ungraded = self
+            .assignment
+            .get_questions()
+            .iter()
+            .filter(|q| !self.assignment.is_graded(self.student.id, q))
+            .count();
+        let msg = format!(
+            "{} has {} question parts that have not been graded. Export anyway?",
+            self.student.name, ungraded
+        );
+        ungraded == 0 || V::confirm(&msg)
+    }
+
+    fn all_ready_to_export(&self) -> bool {
+        let total = self.assignment.num_students() * self.assignment.num_questions();
+        let ungraded = total - self.assignment.total_graded();
+        let msg = format!(
+            "{} of {} question parts have not been graded. Export anyway?",
+            ungraded, total
+        );
+        ungraded == 0 || V::confirm(&msg)
+    }
+
+    fn export_grade_sheets(&self) {
+        let renderer = match V::choose_renderer() {
+            Some(renderer) => renderer,
+            None => return,
+        };
+        let menu = vec![
+            format!("Current Student ({})", self.student.name),
+            "All Students".to_string(),
+            "Cancel".to_string(),
+        ];
+        let header = format!("Export {} Grade Sheets For", renderer.describe());
+        match V::show_menu(&header, &menu) {
+            1 if self.ready_to_export() => Self::report(F::save_grade_sheet(
+                &self.assignment,
+                self.student.id,
+                renderer.as_ref(),
+            )),
+            2 if self.all_ready_to_export() => Self::report(F::save_all_grade_sheets(
+                &self.assignment,
+                renderer.as_ref(),
+            )),
+            _ => {}
+        }
+    }
+
+    fn export_lms_grades(&self) {
+        if let Some(lms) = V::choose_lms() {
+            Self::report(F::save_lms_grades(&self.assignment, lms));
+        }
+    }
+
     fn new_student(&mut self) {
-        self.student = V::new_student(&self.assignment);
-        self.assignment.add_student(&self.student);
+        let id = self
+            .assignment
+            .add_student(V::new_student(&self.assignment));
+        self.student = self.assignment.get_student(id);
         self.student_idx = self.assignment.num_students() - 1;
-        F::save_assignment(&self.assignment);
+        self.save();
+    }
+
+    // Adds the students in a roster file, keeping the current student.
+    fn import_roster(&mut self) {
+        let (filename, columns) = match V::choose_roster() {
+            Some(choice) => choice,
+            None => return,
+        };
+        let text = match F::load_roster(&filename) {
+            Ok(text) => text,
+            Err(e) => return V::show_error(&e),
+        };
+        match roster::import(&mut self.assignment, &text, &columns) {
+            Ok(report) => {
+                V::show_message(&report.describe());
+                if !report.added.is_empty() {
+                    self.save();
+                }
+            }
+            Err(e) => V::show_message(&format!("Could not import {}: {}", filename, e)),
+        }
+    }
+
+    // Saves the assignment and reports if it could not be saved. The
+    // assignment is still in memory so grading can carry on either way.
+    fn save(&self) {
+        Self::report(F::save_assignment(&self.assignment, &self.filename));
+    }
+
+    // Shows the error to the user if a file operation failed.
+    fn report(result: Result<(), FileError>) {
+        if let Err(e) = result {
+            V::show_error(&e);
+        }
     }
 
     fn change_student(&mut self, dx: i32) {
@@ -117,75 +285,572 @@ impl<V: View, F: FileOps> App<V, F> {
         self.student = self.assignment.get_student_at(self.student_idx);
     }
 
-    // Question Menu /////////////////////////////////////////////////////////
-    fn question_menu(&mut self) {
+    fn go_to_student(&mut self) {
+        if let Some(id) = V::go_to_student(&self.assignment) {
+            self.set_student(id);
+        }
+    }
+
+    // Makes the student with the given id the current one.
+    fn set_student(&mut self, id: u64) {
+        let students = self.assignment.get_students();
+        self.student_idx = students.iter().position(|s| s.id == id).unwrap() as u32;
+        self.student = self.assignment.get_student_at(self.student_idx);
+    }
+
+    // Reports Menu //////////////////////////////////////////////////////////
+    fn reports_menu(&self) {
+        let menu = vec![
+            "Show Statistics".to_string(),
+            "Export Statistics CSV".to_string(),
+            "Export Statistics LaTeX".to_string(),
+            "Show Comment Usage".to_string(),
+            "Export Comment Usage CSV".to_string(),
+            "Back".to_string(),
+        ];
+
+        loop {
+            match V::show_menu("Reports", &menu) {
+                1 => V::show_statistics(&self.assignment),
+                2 => Self::report(F::save_statistics(&self.assignment, false)),
+                3 => Self::report(F::save_statistics(&self.assignment, true)),
+                4 => {
+                    if let Some(order) = V::choose_usage_order() {
+                        V::show_comment_usage(&self.assignment, order);
+                    }
+                }
+                5 => {
+                    if let Some(order) = V::choose_usage_order() {
+                        Self::report(F::save_comment_usage(&self.assignment, order));
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    // Student Menu //////////////////////////////////////////////////////////
+    fn student_menu(&mut self) {
+        let menu = vec![
+            "Rename Student".to_string(),
+            "Withdraw Student".to_string(),
+            "Reinstate Student".to_string(),
+            "Delete Student".to_string(),
+            "Submission Time".to_string(),
+            "Extension".to_string(),
+            "Back".to_string(),
+        ];
+
+        loop {
+            let header = format!("Manage Students ({})", self.student.name);
+            match V::show_menu(&header, &menu) {
+                1 => self.rename_student(),
+                2 => self.withdraw_student(),
+                3 => self.reinstate_student(),
+                4 => self.delete_student(),
+                5 => self.submission_time(),
+                6 => self.extension(),
+                _ => break,
+            }
+        }
+    }
+
+    fn rename_student(&mut self) {
+        if let Some(name) = V::rename_student(&self.student) {
+            self.assignment.rename_student(self.student.id, &name);
+            self.students_changed();
+        }
+    }
+
+    fn withdraw_student(&mut self) {
+        if self.assignment.num_students() == 1 {
+            V::show_message("The assignment must have at least one student being graded");
+            return;
+        }
+        let msg = format!(
+            "Withdraw {}? Their marks are kept and they can be reinstated.",
+            self.student.name
+        );
+        if V::confirm(&msg) {
+            self.assignment.withdraw_student(self.student.id);
+            self.students_changed();
+        }
+    }
+
+    fn reinstate_student(&mut self) {
+        let withdrawn = self.assignment.get_withdrawn_students();
+        if withdrawn.is_empty() {
+            V::show_message("No students have withdrawn");
+            return;
+        }
+        if let Some(id) = V::choose_student(&withdrawn, "Reinstate Student") {
+            self.assignment.reinstate_student(id);
+            self.students_changed();
+        }
+    }
+
+    fn delete_student(&mut self) {
+        if self.assignment.num_students() == 1 {
+            V::show_message("The assignment must have at least one student being graded");
+            return;
+        }
+        let msg = format!(
+            "Delete {} and all of their marks? This can not be undone.",
+            self.student.name
+        );
+        if V::confirm(&msg) {
+            self.assignment.remove_student(self.student.id);
+            self.students_changed();
+        }
+    }
+
+    fn submission_time(&mut self) {
+        let header = format!("Submission Time For {}", self.student.name);
+        let submitted = V::choose_date_time(&header, self.student.submitted);
+        self.assignment.set_submitted(self.student.id, submitted);
+        self.students_changed();
+    }
+
+    fn extension(&mut self) {
+        let header = format!("Extended Due Date For {}", self.student.name);
+        let extension = V::choose_date_time(&header, self.student.extension);
+        self.assignment.set_extension(self.student.id, extension);
+        self.students_changed();
+    }
+
+    // Keeps the current student valid after students have changed and saves.
+    // Stays on the same student if they are still being graded.
+    fn students_changed(&mut self) {
+        let students = self.assignment.get_students();
+        match students.iter().position(|s| s.id == self.student.id) {
+            Some(idx) => self.student_idx = idx as u32,
+            None if self.student_idx >= students.len() as u32 => {
+                self.student_idx = students.len() as u32 - 1
+            }
+            None => {}
+        }
+        self.student = self.assignment.get_student_at(self.student_idx);
+        self.save();
+    }
+
+    // Structure Menu ////////////////////////////////////////////////////////
+    fn structure_menu(&mut self) {
         let menu = vec![
-            "Add New Comment".to_string(),
-            "Add Existing Comment".to_string(),
-            "Edit Comment".to_string(),
-            "Remove Comment".to_string(),
-            "Next Question".to_string(),
-            "Prev Question".to_string(),
+            "Add Question Part".to_string(),
+            "Remove Question Part".to_string(),
+            "Change Marks For Part".to_string(),
+            "Renumber Question Part".to_string(),
+            "Assignment Bonus Policy".to_string(),
+            "Question Bonus Policy".to_string(),
+            "Scaling".to_string(),
+            "Due Date".to_string(),
+            "Late Policy".to_string(),
+            "LaTeX Template".to_string(),
+            "Raw LaTeX Comments".to_string(),
+            "PDF Engine".to_string(),
             "Back".to_string(),
         ];
 
         loop {
-            V::show_question_info(&self.assignment, &self.student, &self.question);
-            let header = format!("Grading: {}", self.student);
+            V::show_structure(&self.assignment);
+            let choice = V::show_menu("Edit Structure", &menu);
+            match choice {
+                1 => self.add_question_part(),
+                2 => self.remove_question_part(),
+                3 => self.change_out_of(),
+                4 => self.renumber_question(),
+                5 => self.assignment_bonus_policy(),
+                6 => self.question_bonus_policy(),
+                7 => self.scaling_menu(),
+                8 => self.due_date(),
+                9 => self.late_policy(),
+                10 => self.latex_template(),
+                11 => self.raw_latex_comment(),
+                12 => self.pdf_engine(),
+                _ => break,
+            }
+        }
+    }
+
+    fn add_question_part(&mut self) {
+        if let Some(q) = V::new_question_part(&self.assignment) {
+            self.assignment.insert_question(q.num, q.part, q.out_of);
+            self.structure_changed();
+        }
+    }
+
+    fn remove_question_part(&mut self) {
+        if self.assignment.num_questions() == 1 {
+            V::show_message("The assignment must have at least one question part");
+            return;
+        }
+        if let Some(q) = V::choose_question(&self.assignment, "Remove Question Part") {
+            let msg = format!(
+                "Remove {}.{} and all of its comments? This can not be undone.",
+                q.num, q.part
+            );
+            if V::confirm(&msg) {
+                self.assignment.remove_question(&q);
+                self.structure_changed();
+            }
+        }
+    }
+
+    fn change_out_of(&mut self) {
+        if let Some(q) = V::choose_question(&self.assignment, "Change Marks For Part") {
+            if let Some(out_of) = V::new_out_of(&q) {
+                self.assignment.set_out_of(&q, out_of);
+                self.structure_changed();
+            }
+        }
+    }
+
+    fn renumber_question(&mut self) {
+        if let Some(q) = V::choose_question(&self.assignment, "Renumber Question Part") {
+            if let Some((num, part)) = V::renumber_question(&self.assignment, &q) {
+                self.assignment.renumber_question(&q, num, part);
+                if q.same_part(&self.question) {
+                    self.question = Question::new(num, part, q.out_of);
+                }
+                self.structure_changed();
+            }
+        }
+    }
+
+    fn assignment_bonus_policy(&mut self) {
+        let header = format!(
+            "Assignment Bonus Policy ({})",
+            self.assignment.bonus_policy().describe()
+        );
+        let menu = vec![
+            BonusPolicy::Cap.describe().to_string(),
+            BonusPolicy::AllowOver.describe().to_string(),
+            "Cancel".to_string(),
+        ];
+        let policy = match V::show_menu(&header, &menu) {
+            1 => BonusPolicy::Cap,
+            2 => BonusPolicy::AllowOver,
+            _ => return,
+        };
+        self.assignment.set_bonus_policy(policy);
+        self.save();
+    }
+
+    fn question_bonus_policy(&mut self) {
+        if let Some(q) = V::choose_question(&self.assignment, "Question Bonus Policy") {
+            let header = format!(
+                "Bonus Policy For {}.{} ({})",
+                q.num,
+                q.part,
+                self.assignment.question_bonus_policy(&q).describe()
+            );
+            let menu = vec![
+                "Same As Assignment".to_string(),
+                BonusPolicy::Cap.describe().to_string(),
+                BonusPolicy::AllowOver.describe().to_string(),
+                "Cancel".to_string(),
+            ];
+            let policy = match V::show_menu(&header, &menu) {
+                1 => None,
+                2 => Some(BonusPolicy::Cap),
+                3 => Some(BonusPolicy::AllowOver),
+                _ => return,
+            };
+            self.assignment.set_question_bonus_policy(&q, policy);
+            self.save();
+        }
+    }
+
+    fn due_date(&mut self) {
+        let due = V::choose_date_time("Due Date", self.assignment.due_date());
+        self.assignment.set_due_date(due);
+        self.save();
+    }
+
+    fn late_policy(&mut self) {
+        if let Some(policy) = V::new_late_policy(self.assignment.late_policy()) {
+            self.assignment.set_late_policy(policy);
+            self.save();
+        }
+    }
+
+    fn latex_template(&mut self) {
+        let current = self.assignment.latex_template().unwrap_or("").to_string();
+        let path = V::choose_template(&current);
+        if let Some(p) = path.as_ref() {
+            if let Err(e) = F::load_template(p) {
+                V::show_error(&e);
+                return;
+            }
+        }
+        self.assignment.set_latex_template(path);
+        self.save();
+    }
+
+    fn raw_latex_comment(&mut self) {
+        let q = match V::choose_question(&self.assignment, "Raw LaTeX Comments") {
+            Some(q) => q,
+            None => return,
+        };
+        let header = "Toggle Raw LaTeX (raw comments are not escaped)";
+        if let Some(id) = V::choose_comment(&self.assignment, &q, header) {
+            let raw = !self
+                .assignment
+                .comments_for(&q)
+                .iter()
+                .any(|c| c.id == id && c.raw_latex);
+            self.assignment.set_comment_raw_latex(&q, id, raw);
+            V::show_message(if raw {
+                "The comment will be put in grade sheets as LaTeX"
+            } else {
+                "The comment will be escaped in grade sheets"
+            });
+            self.save();
+        }
+    }
+
+    fn pdf_engine(&mut self) {
+        let current = self
+            .assignment
+            .pdf_engine()
+            .map_or("Do Not Compile", |e| e.name());
+        let header = format!("Compile LaTeX Grade Sheets To PDF With ({})", current);
+        let engines = LatexEngine::all();
+        let mut menu = vec!["Do Not Compile".to_string()];
+        menu.extend(engines.iter().map(|e| e.name().to_string()));
+        menu.push("Cancel".to_string());
+
+        let engine = match V::show_menu(&header, &menu) as usize {
+            1 => None,
+            n if n >= 2 && n - 2 < engines.len() => Some(engines[n - 2]),
+            _ => return,
+        };
+        self.assignment.set_pdf_engine(engine);
+        self.save();
+    }
+
+    fn scaling_menu(&mut self) {
+        let menu = vec![
+            "Add Scaling Step".to_string(),
+            "Remove Last Step".to_string(),
+            "Clear Scaling".to_string(),
+            "Preview Scaling".to_string(),
+            "Back".to_string(),
+        ];
+
+        loop {
+            let header = format!("Scaling ({} steps)", self.assignment.scaling().len());
+            let mut steps = self.assignment.scaling().to_vec();
+            match V::show_menu(&header, &menu) {
+                1 => match V::new_scaling(&self.assignment) {
+                    Some(step) => steps.push(step),
+                    None => continue,
+                },
+                2 | 3 if steps.is_empty() => {
+                    V::show_message("There is no scaling");
+                    continue;
+                }
+                2 => {
+                    steps.pop();
+                }
+                3 => steps.clear(),
+                4 => {
+                    V::show_scaling_preview(&self.assignment, &steps);
+                    continue;
+                }
+                _ => break,
+            }
+            self.change_scaling(steps);
+        }
+    }
+
+    // Previews the new scaling steps and only uses them once confirmed.
+    fn change_scaling(&mut self, steps: Vec<Scaling>) {
+        V::show_scaling_preview(&self.assignment, &steps);
+        if V::confirm("Use this scaling?") {
+            self.assignment.set_scaling(steps);
+            self.save();
+        }
+    }
+
+    // Keeps the same question current after the questions have changed, or
+    // the one in its place if it was removed, and saves the new structure.
+    fn structure_changed(&mut self) {
+        let questions = self.assignment.get_questions();
+        match questions.iter().position(|q| q.same_part(&self.question)) {
+            Some(idx) => self.question_idx = idx as u32,
+            None if self.question_idx >= self.assignment.num_questions() => {
+                self.question_idx = self.assignment.num_questions() - 1;
+            }
+            None => {}
+        }
+        self.question = self.assignment.get_question_at(self.question_idx);
+        self.save();
+    }
+
+    // Grading Menus /////////////////////////////////////////////////////////
+    fn question_menu(&mut self) {
+        loop {
+            let menu = vec![
+                "Add New Comment".to_string(),
+                "Add Existing Comment".to_string(),
+                "Edit Comment".to_string(),
+                "Remove Comment".to_string(),
+                self.toggle_graded_label(),
+                "Next Question".to_string(),
+                "Prev Question".to_string(),
+                "Go To Question".to_string(),
+                "Next Ungraded".to_string(),
+                "Undo".to_string(),
+                "Redo".to_string(),
+                "Back".to_string(),
+            ];
+
+            V::show_question_info(&self.assignment, self.student.id, &self.question);
+            let header = format!("Grading: {}", self.student.name);
             let choice = V::show_menu(&header, &menu);
             match choice {
                 1 => self.add_new_comment(),
                 2 => self.add_existing_comment(),
                 3 => self.edit_comment(),
                 4 => self.remove_comment(),
-                5 => self.change_question(1),
-                6 => self.change_question(-1),
+                5 => self.toggle_graded(),
+                6 => self.change_question(1),
+                7 => self.change_question(-1),
+                8 => self.go_to_question(),
+                9 => self.next_ungraded(false),
+                10 => self.undo(),
+                11 => self.redo(),
                 _ => break,
             }
         }
     }
 
-    fn add_new_comment(&mut self) {
-        match V::new_comment() {
-            Some((deduct, text)) => {
-                self.assignment
-                    .add_comment(&self.student, &self.question, deduct, text);
-                F::save_assignment(&self.assignment);
+    // Grades a chosen question for each student in turn, so that the same
+    // comments are given consistently.
+    fn grade_by_question_menu(&mut self) {
+        match V::choose_question(&self.assignment, "Grade By Question") {
+            Some(question) => self.set_question(&question),
+            None => return,
+        }
+
+        loop {
+            let menu = vec![
+                "Add New Comment".to_string(),
+                "Add Existing Comment".to_string(),
+                "Edit Comment".to_string(),
+                "Remove Comment".to_string(),
+                self.toggle_graded_label(),
+                "Next Student".to_string(),
+                "Prev Student".to_string(),
+                "Go To Student".to_string(),
+                "Next Ungraded".to_string(),
+                "Undo".to_string(),
+                "Redo".to_string(),
+                "Back".to_string(),
+            ];
+
+            V::show_comment_bank(&self.assignment, self.student.id, &self.question);
+            let header = format!("Grading {}: {}", self.question.label(), self.student.name);
+            match V::show_menu(&header, &menu) {
+                1 => self.add_new_comment(),
+                2 => self.add_existing_comment(),
+                3 => self.edit_comment(),
+                4 => self.remove_comment(),
+                5 => self.toggle_graded(),
+                6 => self.change_student(1),
+                7 => self.change_student(-1),
+                8 => self.go_to_student(),
+                9 => self.next_ungraded(true),
+                10 => self.undo(),
+                11 => self.redo(),
+                _ => break,
             }
-            _ => (),
         }
     }
 
-    fn add_existing_comment(&mut self) {
-        match V::add_existing_comment(&self.assignment, &self.student, &self.question) {
-            Some(id) => {
-                self.assignment
-                    .add_to_comment(&self.student, &self.question, id);
-                F::save_assignment(&self.assignment);
+    fn toggle_graded_label(&self) -> String {
+        if self.assignment.is_graded(self.student.id, &self.question) {
+            "Mark Not Graded".to_string()
+        } else {
+            "Mark Graded".to_string()
+        }
+    }
+
+    fn toggle_graded(&mut self) {
+        let graded = self.assignment.is_graded(self.student.id, &self.question);
+        self.assignment
+            .set_graded(self.student.id, &self.question, !graded);
+        self.save();
+    }
+
+    // Moves to the next student and question that have not been graded,
+    // keeping to the current question first if grading by question.
+    fn next_ungraded(&mut self, by_question: bool) {
+        let next = self
+            .assignment
+            .next_ungraded(self.student.id, &self.question, by_question);
+        match next {
+            Some((student, question)) => {
+                self.set_student(student);
+                self.set_question(&question);
             }
-            _ => (),
+            None => V::show_message("Everything has been graded"),
+        }
+    }
+
+    fn add_new_comment(&mut self) {
+        if let Some((deduct, text)) = V::new_comment() {
+            self.assignment
+                .add_comment(self.student.id, &self.question, deduct, text);
+            self.save();
+        }
+    }
+
+    fn add_existing_comment(&mut self) {
+        if let Some(id) = V::add_existing_comment(&self.assignment, self.student.id, &self.question)
+        {
+            self.assignment
+                .add_to_comment(self.student.id, &self.question, id);
+            self.save();
         }
     }
 
     fn edit_comment(&mut self) {
-        match V::edit_comment(&self.assignment, &self.student, &self.question) {
-            Some((deduct, text, id)) => {
-                self.assignment
-                    .edit_comment(&self.question, id, deduct, text);
-                F::save_assignment(&self.assignment);
-            }
-            _ => (),
+        if let Some((deduct, text, id)) =
+            V::edit_comment(&self.assignment, self.student.id, &self.question)
+        {
+            self.assignment
+                .edit_comment(&self.question, id, deduct, text);
+            self.save();
         };
     }
 
     fn remove_comment(&mut self) {
-        match V::remove_comment(&self.assignment, &self.student, &self.question) {
-            Some(id) => {
-                self.assignment
-                    .remove_from_comment(&self.student, &self.question, id);
-                F::save_assignment(&self.assignment);
+        if let Some(id) = V::remove_comment(&self.assignment, self.student.id, &self.question) {
+            self.assignment
+                .remove_from_comment(self.student.id, &self.question, id);
+            self.save();
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.assignment.undo() {
+            Some(action) => {
+                V::show_message(&format!("Undid: {}", action.describe(&self.assignment)));
+                self.save();
             }
-            _ => (),
+            None => V::show_message("Nothing to undo"),
+        }
+    }
+
+    fn redo(&mut self) {
+        match self.assignment.redo() {
+            Some(action) => {
+                V::show_message(&format!("Redid: {}", action.describe(&self.assignment)));
+                self.save();
+            }
+            None => V::show_message("Nothing to redo"),
         }
     }
 
@@ -200,13 +865,29 @@ impl<V: View, F: FileOps> App<V, F> {
         }
         self.question = self.assignment.get_question_at(self.question_idx);
     }
+
+    fn go_to_question(&mut self) {
+        if let Some(question) = V::go_to_question(&self.assignment) {
+            self.set_question(&question);
+        }
+    }
+
+    // Makes the given question the current one.
+    fn set_question(&mut self, question: &Question) {
+        let questions = self.assignment.get_questions();
+        let idx = questions.iter().position(|q| q.same_part(question));
+        self.question_idx = idx.unwrap() as u32;
+        self.question = self.assignment.get_question_at(self.question_idx);
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::data::roster::RosterColumns;
     use crate::io::MockFileOps;
     use crate::io::MockView;
+    use crate::io::StorageFormat;
     use mockall::Sequence;
 
     fn make_test_asn() -> Assignment {
@@ -219,17 +900,17 @@ mod test {
         asn.add_question(q2.num, q2.part, q2.out_of);
         asn.add_question(q3.num, q3.part, q3.out_of);
 
-        asn.add_student("Issac Newton");
-        asn.add_student("Albert Einstein");
-        asn.add_student("Marie Currie");
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        let einstein = asn.add_student(Student::new("Albert Einstein"));
+        let currie = asn.add_student(Student::new("Marie Currie"));
 
-        asn.add_comment("Albert Einstein", &q1, 3.0, "Amateurish work".to_string());
-        asn.add_comment("Albert Einstein", &q2, 5.0, "Not correct".to_string());
-        asn.add_comment("Marie Currie", &q1, 1.0, "On the right track".to_string());
+        asn.add_comment(einstein, &q1, 3.0, "Amateurish work".to_string());
+        asn.add_comment(einstein, &q2, 5.0, "Not correct".to_string());
+        asn.add_comment(currie, &q1, 1.0, "On the right track".to_string());
 
-        asn.add_comment("Issac Newton", &q2, 1.5, "Amateurish work".to_string());
-        asn.add_comment("Issac Newton", &q2, 2.0, "Try harder".to_string());
-        asn.add_comment("Issac Newton", &q3, 3.0, "Mind the apples".to_string());
+        asn.add_comment(newton, &q2, 1.5, "Amateurish work".to_string());
+        asn.add_comment(newton, &q2, 2.0, "Try harder".to_string());
+        asn.add_comment(newton, &q3, 3.0, "Mind the apples".to_string());
 
         asn
     }
@@ -244,12 +925,52 @@ mod test {
         assert_eq!(app.assignment.course, "CS 1000".to_string());
         assert_eq!(app.student_idx, 0);
         assert_eq!(app.question_idx, 0);
-        assert_eq!(app.assignment.get_student_at(0), "Issac Newton");
+        assert_eq!(app.assignment.get_student_at(0).name, "Issac Newton");
         assert_eq!(app.assignment.get_question_at(0), Question::new(1, 1, 5));
     }
 
     #[test]
-    fn the_assignment_menu_displays_grade_sheet_for_5() {
+    fn it_starts_a_new_assignment_at_its_first_roster_student() {
+        let ctx_ca = MockView::create_assignment_context();
+        let ctx_csf = MockView::choose_storage_format_context();
+        let ctx_c = MockView::confirm_context();
+        let ctx_cr = MockView::choose_roster_context();
+        let ctx_lr = MockFileOps::load_roster_context();
+        let ctx_sm = MockView::show_message_context();
+        let ctx_sa = MockFileOps::save_assignment_context();
+        ctx_ca.expect().times(1).returning(|| {
+            let mut asn = Assignment::new("Lab 1".to_string(), "CS 1000".to_string());
+            asn.add_question(1, 1, 5);
+            asn
+        });
+        ctx_csf.expect().times(1).return_const(StorageFormat::Json);
+        ctx_c.expect().times(1).return_const(true);
+        ctx_cr
+            .expect()
+            .times(1)
+            .returning(|| Some(("roster.csv".to_string(), RosterColumns::default())));
+        ctx_lr
+            .expect()
+            .times(1)
+            .returning(|_| Ok("Name\nIssac Newton\nAlbert Einstein\nMarie Currie\n".to_string()));
+        ctx_sm.expect().return_const(());
+        ctx_sa.expect().returning(|_, _| Ok(()));
+
+        let mut app = App::<MockView, MockFileOps>::new();
+        app.set_assignment(make_test_asn());
+        app.set_student(2);
+        app.change_question(-1);
+        app.new_assignment();
+
+        assert_eq!(app.student_idx, 0);
+        assert_eq!(app.question_idx, 0);
+        assert_eq!(app.student.name, "Issac Newton");
+        app.change_student(-1);
+        assert_eq!(app.student.name, "Marie Currie");
+    }
+
+    #[test]
+    fn the_assignment_menu_displays_grade_sheet_for_8() {
         // Remove this test and just test the other functions. The menus are
         // too suceptible to changes and doing the loops is a pain in tests.
         // the logic for the tasks is more important for the running of the app.
@@ -263,7 +984,7 @@ mod test {
             .expect()
             .times(1)
             .in_sequence(&mut seq)
-            .return_const(5u32);
+            .return_const(8u32);
         ctx_sgs
             .expect()
             .times(1)
@@ -273,9 +994,162 @@ mod test {
             .expect()
             .times(1)
             .in_sequence(&mut seq)
-            .return_const(8u32);
+            .return_const(18u32);
 
         let mut app = App::<MockView, MockFileOps>::new();
         app.asn_menu();
     }
+
+    #[test]
+    fn it_shows_grading_progress_for_each_question() {
+        let mut app = App::<MockView, MockFileOps>::new();
+        app.set_assignment(make_test_asn());
+        assert_eq!(app.progress(), "Graded 5/9 (1.1 2/3, 1.2 2/3, 2.1 1/3)");
+    }
+
+    #[test]
+    fn it_goes_straight_to_a_chosen_student() {
+        let ctx_gts = MockView::go_to_student_context();
+        ctx_gts.expect().times(1).return_const(Some(2u64));
+
+        let mut app = App::<MockView, MockFileOps>::new();
+        app.set_assignment(make_test_asn());
+        app.go_to_student();
+
+        assert_eq!(app.student_idx, 2);
+        assert_eq!(app.student.name, "Marie Currie");
+    }
+
+    #[test]
+    fn it_makes_a_chosen_question_the_current_one() {
+        let mut app = App::<MockView, MockFileOps>::new();
+        app.set_assignment(make_test_asn());
+        app.set_question(&Question::new(2, 1, 10));
+
+        assert_eq!(app.question_idx, 2);
+        assert_eq!(app.question, Question::new(2, 1, 10));
+    }
+
+    #[test]
+    fn it_grades_a_chosen_question_for_each_student_in_turn() {
+        let mut seq = Sequence::new();
+        let ctx_cq = MockView::choose_question_context();
+        let ctx_scb = MockView::show_comment_bank_context();
+        let ctx_sm = MockView::show_menu_context();
+        ctx_cq
+            .expect()
+            .times(1)
+            .returning(|_, _| Some(Question::new(1, 2, 5)));
+        for (student, choice) in [(0u64, 6u32), (1, 7), (0, 7), (2, 12)].iter() {
+            let student = *student;
+            ctx_scb
+                .expect()
+                .withf(move |_, s, q| *s == student && q.same_part(&Question::new(1, 2, 5)))
+                .times(1)
+                .in_sequence(&mut seq)
+                .return_const(());
+            ctx_sm
+                .expect()
+                .times(1)
+                .in_sequence(&mut seq)
+                .return_const(*choice);
+        }
+
+        let mut app = App::<MockView, MockFileOps>::new();
+        app.set_assignment(make_test_asn());
+        app.grade_by_question_menu();
+
+        assert_eq!(app.student.name, "Marie Currie");
+        assert_eq!(app.question, Question::new(1, 2, 5));
+    }
+
+    #[test]
+    fn it_keeps_the_current_question_when_parts_are_renumbered() {
+        let mut seq = Sequence::new();
+        let ctx_cq = MockView::choose_question_context();
+        let ctx_rq = MockView::renumber_question_context();
+        let ctx_sa = MockFileOps::save_assignment_context();
+        for (from, to) in [((1, 1), (3, 1)), ((3, 1), (1, 1))].iter() {
+            let (from, to) = (*from, *to);
+            ctx_cq
+                .expect()
+                .times(1)
+                .in_sequence(&mut seq)
+                .returning(move |_, _| Some(Question::new(from.0, from.1, 5)));
+            ctx_rq
+                .expect()
+                .times(1)
+                .in_sequence(&mut seq)
+                .return_const(Some(to));
+        }
+        ctx_sa.expect().returning(|_, _| Ok(()));
+
+        let mut app = App::<MockView, MockFileOps>::new();
+        app.set_assignment(make_test_asn());
+        app.set_question(&Question::new(2, 1, 10));
+        app.renumber_question();
+        assert_eq!(app.question_idx, 1);
+        assert_eq!(app.question, Question::new(2, 1, 10));
+
+        app.set_question(&Question::new(3, 1, 5));
+        app.renumber_question();
+        assert_eq!(app.question_idx, 0);
+        assert_eq!(app.question, Question::new(1, 1, 5));
+    }
+
+    #[test]
+    fn it_reports_a_failed_save_instead_of_crashing() {
+        let ctx_sa = MockFileOps::save_assignment_context();
+        let ctx_se = MockView::show_error_context();
+
+        ctx_sa.expect().times(1).returning(|_, f| {
+            Err(FileError::WriteFailed(
+                f.to_string(),
+                "disk full".to_string(),
+            ))
+        });
+        ctx_se
+            .expect()
+            .withf(|e| {
+                *e == FileError::WriteFailed("none.emark".to_string(), "disk full".to_string())
+            })
+            .times(1)
+            .return_const(());
+
+        let app = App::<MockView, MockFileOps>::new();
+        app.save();
+    }
+
+    #[test]
+    fn it_previews_scaling_and_keeps_the_old_scaling_if_declined() {
+        let ctx_ssp = MockView::show_scaling_preview_context();
+        let ctx_c = MockView::confirm_context();
+        ctx_ssp
+            .expect()
+            .withf(|_, steps| steps == [Scaling::Add(5.0), Scaling::SquareRoot])
+            .times(1)
+            .return_const(());
+        ctx_c.expect().times(1).return_const(false);
+
+        let mut app = App::<MockView, MockFileOps>::new();
+        app.set_assignment(make_test_asn());
+        app.assignment.set_scaling(vec![Scaling::Add(5.0)]);
+        app.change_scaling(vec![Scaling::Add(5.0), Scaling::SquareRoot]);
+
+        assert_eq!(app.assignment.scaling(), [Scaling::Add(5.0)]);
+    }
+
+    #[test]
+    fn it_exports_nothing_if_no_grade_sheet_format_is_chosen() {
+        let ctx_cr = MockView::choose_renderer_context();
+        let ctx_sgs = MockFileOps::save_grade_sheet_context();
+        let ctx_sags = MockFileOps::save_all_grade_sheets_context();
+        ctx_cr.expect().times(1).returning(|| None);
+        ctx_sgs.expect().never();
+        ctx_sags.expect().never();
+
+        let mut app = App::<MockView, MockFileOps>::new();
+        app.set_assignment(make_test_asn());
+        app.export_grade_sheets();
+    }
 }
@@ -0,0 +1,624 @@
+use crate::data::latex::LatexEngine;
+use crate::data::lms::Lms;
+use crate::data::roster::{self, RosterColumns};
+use crate::data::usage::UsageOrder;
+use crate::data::{latex, Assignment, Student};
+use crate::io::render;
+use crate::io::{FileError, FileOps};
+
+/// Exit code for a command that finished successfully.
+pub const EXIT_OK: i32 = 0;
+/// Exit code for a command that failed reading or writing a file.
+pub const EXIT_FILE_ERROR: i32 = 1;
+/// Exit code for a command that was used incorrectly.
+pub const EXIT_USAGE: i32 = 2;
+/// Exit code for a command that does not make sense for the assignment,
+/// like adding a student that is already there.
+pub const EXIT_INVALID: i32 = 3;
+
+const USAGE: &str = "Usage: easy_mark [COMMAND]
+
+Runs the interactive grader when no command is given.
+
+Commands:
+  export-latex FILE [STUDENT]  Write LaTeX grade sheets for all students, or one
+                               given by student number or name
+  export-sheets FILE FORMAT [STUDENT]
+                               Write grade sheets as latex, markdown, or html
+                               for all students, or one given by number or name
+  export-csv FILE [--long]     Write the gradebook CSV, or the comments CSV
+  export-lms FILE SYSTEM       Write grades for moodle, canvas, or blackboard
+  export-stats FILE [--latex]  Write the statistics report as CSV, or LaTeX
+  export-usage FILE [ORDER]    Write how often each comment was used as CSV,
+                               ordered by created, students, or marks lost
+  set-template FILE [TEMPLATE]
+                               Lay out LaTeX grade sheets with a template
+                               file, or the default layout if none is given
+  set-engine FILE [ENGINE]     Compile LaTeX grade sheets to PDF with pdflatex,
+                               xelatex, or tectonic, or stop if none is given
+  default-template             Print the default LaTeX grade sheet template
+  summary FILE                 Print every student's total
+  add-student FILE NAME [NUMBER [EMAIL [SECTION]]]
+                               Add a student to the assignment
+  import-roster FILE ROSTER [--name H] [--number H] [--email H] [--section H]
+                               Add the students in a CSV roster, using the
+                               column with header H for each detail
+  help                         Print this message";
+
+/// A command given on the command line.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    ExportSheets {
+        file: String,
+        format: String,
+        student: Option<String>,
+    },
+    ExportCsv {
+        file: String,
+        long: bool,
+    },
+    ExportLms {
+        file: String,
+        lms: Lms,
+    },
+    ExportStats {
+        file: String,
+        latex: bool,
+    },
+    ExportUsage {
+        file: String,
+        order: UsageOrder,
+    },
+    SetTemplate {
+        file: String,
+        template: Option<String>,
+    },
+    DefaultTemplate,
+    SetEngine {
+        file: String,
+        engine: Option<LatexEngine>,
+    },
+    Summary {
+        file: String,
+    },
+    AddStudent {
+        file: String,
+        student: Student,
+    },
+    ImportRoster {
+        file: String,
+        roster: String,
+        columns: RosterColumns,
+    },
+    Help,
+}
+
+// The ways a command can fail, which decide the exit code.
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Invalid(String),
+    File(FileError),
+}
+
+impl From<FileError> for CliError {
+    fn from(e: FileError) -> CliError {
+        CliError::File(e)
+    }
+}
+
+/// Runs the command given by the arguments, not including the program name,
+/// without prompting for anything. Returns the exit code for the process.
+pub fn run<F: FileOps>(args: &[String]) -> i32 {
+    let result = parse(args)
+        .map_err(CliError::Usage)
+        .and_then(|cmd| execute::<F>(cmd));
+
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(CliError::Usage(msg)) => {
+            eprintln!("easy_mark: {}\n\n{}", msg, USAGE);
+            EXIT_USAGE
+        }
+        Err(CliError::Invalid(msg)) => {
+            eprintln!("easy_mark: {}", msg);
+            EXIT_INVALID
+        }
+        Err(CliError::File(e)) => {
+            eprintln!("easy_mark: {}", e);
+            EXIT_FILE_ERROR
+        }
+    }
+}
+
+/// Parses the arguments, not including the program name, into a command.
+/// Returns a message describing the problem if they are not valid.
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    match args.as_slice() {
+        ["export-latex", file, student @ ..] if student.len() <= 1 => Ok(Command::ExportSheets {
+            file: file.to_string(),
+            format: "latex".to_string(),
+            student: student.first().map(|s| s.to_string()),
+        }),
+        ["export-sheets", file, format, student @ ..] if student.len() <= 1 => {
+            match render::find_renderer(format) {
+                Some(renderer) => Ok(Command::ExportSheets {
+                    file: file.to_string(),
+                    format: renderer.name().to_string(),
+                    student: student.first().map(|s| s.to_string()),
+                }),
+                None => Err(format!("unknown grade sheet format '{}'", format)),
+            }
+        }
+        ["export-csv", file] => Ok(Command::ExportCsv {
+            file: file.to_string(),
+            long: false,
+        }),
+        ["export-csv", file, "--long"] => Ok(Command::ExportCsv {
+            file: file.to_string(),
+            long: true,
+        }),
+        ["export-lms", file, system] => match Lms::from_name(system) {
+            Some(lms) => Ok(Command::ExportLms {
+                file: file.to_string(),
+                lms,
+            }),
+            None => Err(format!("unknown LMS '{}'", system)),
+        },
+        ["export-stats", file] => Ok(Command::ExportStats {
+            file: file.to_string(),
+            latex: false,
+        }),
+        ["export-stats", file, "--latex"] => Ok(Command::ExportStats {
+            file: file.to_string(),
+            latex: true,
+        }),
+        ["export-usage", file] => Ok(Command::ExportUsage {
+            file: file.to_string(),
+            order: UsageOrder::MarksLost,
+        }),
+        ["export-usage", file, order] => match UsageOrder::from_name(order) {
+            Some(order) => Ok(Command::ExportUsage {
+                file: file.to_string(),
+                order,
+            }),
+            None => Err(format!("unknown order '{}'", order)),
+        },
+        ["set-template", file] => Ok(Command::SetTemplate {
+            file: file.to_string(),
+            template: None,
+        }),
+        ["set-template", file, template] => Ok(Command::SetTemplate {
+            file: file.to_string(),
+            template: Some(template.to_string()),
+        }),
+        ["default-template"] => Ok(Command::DefaultTemplate),
+        ["set-engine", file] => Ok(Command::SetEngine {
+            file: file.to_string(),
+            engine: None,
+        }),
+        ["set-engine", file, engine] => match LatexEngine::from_name(engine) {
+            Some(engine) => Ok(Command::SetEngine {
+                file: file.to_string(),
+                engine: Some(engine),
+            }),
+            None => Err(format!("unknown LaTeX engine '{}'", engine)),
+        },
+        ["summary", file] => Ok(Command::Summary {
+            file: file.to_string(),
+        }),
+        ["add-student", file, name, details @ ..] if details.len() <= 3 => {
+            let mut student = Student::new(name);
+            let detail = |i: usize| details.get(i).unwrap_or(&"").to_string();
+            student.number = detail(0);
+            student.email = detail(1);
+            student.section = detail(2);
+            Ok(Command::AddStudent {
+                file: file.to_string(),
+                student,
+            })
+        }
+        ["import-roster", file, roster, options @ ..] => Ok(Command::ImportRoster {
+            file: file.to_string(),
+            roster: roster.to_string(),
+            columns: parse_columns(options)?,
+        }),
+        ["help"] | ["--help"] | ["-h"] => Ok(Command::Help),
+        [cmd, ..] => Err(format!("invalid use of '{}'", cmd)),
+        [] => Err("no command given".to_string()),
+    }
+}
+
+// Parses the options giving the roster column headers, starting from the
+// default headers.
+fn parse_columns(options: &[&str]) -> Result<RosterColumns, String> {
+    let mut columns = RosterColumns::default();
+    for pair in options.chunks(2) {
+        let header = match pair {
+            [_, header] => header.to_string(),
+            _ => return Err(format!("missing column header for '{}'", pair[0])),
+        };
+        match pair[0] {
+            "--name" => columns.name = header,
+            "--number" => columns.number = header,
+            "--email" => columns.email = header,
+            "--section" => columns.section = header,
+            option => return Err(format!("unknown option '{}'", option)),
+        }
+    }
+    Ok(columns)
+}
+
+/// A summary of an assignment with each student's total after any scaling.
+pub fn summary(assignment: &Assignment) -> String {
+    let out_of = assignment.scaled_out_of();
+    let mut lines = vec![
+        format!("{} - {}", assignment.course, assignment.title),
+        format!(
+            "{} students, {} question parts, out of {}",
+            assignment.num_students(),
+            assignment.num_questions(),
+            out_of
+        ),
+    ];
+    let totals = assignment.scaled_totals();
+    for s in assignment.get_students().iter() {
+        lines.push(format!("{}\t{}/{}", s.name, totals[&s.id], out_of));
+    }
+    lines.join("\n")
+}
+
+fn execute<F: FileOps>(cmd: Command) -> Result<(), CliError> {
+    match cmd {
+        Command::ExportSheets {
+            file,
+            format,
+            student,
+        } => {
+            let renderer = render::find_renderer(&format).ok_or_else(|| {
+                CliError::Usage(format!("unknown grade sheet format '{}'", format))
+            })?;
+            let asn = F::load_assignment(&file)?;
+            let student = match student {
+                Some(s) => Some(find_student(&asn, &s)?),
+                None => None,
+            };
+            if let Some(warning) = ungraded_warning(&asn, student) {
+                eprintln!("easy_mark: warning: {}", warning);
+            }
+            match student {
+                Some(id) => F::save_grade_sheet(&asn, id, renderer.as_ref())?,
+                None => F::save_all_grade_sheets(&asn, renderer.as_ref())?,
+            }
+        }
+        Command::ExportCsv { file, long } => {
+            F::save_gradebook(&F::load_assignment(&file)?, long)?;
+        }
+        Command::ExportLms { file, lms } => {
+            F::save_lms_grades(&F::load_assignment(&file)?, lms)?;
+        }
+        Command::ExportStats { file, latex } => {
+            F::save_statistics(&F::load_assignment(&file)?, latex)?;
+        }
+        Command::ExportUsage { file, order } => {
+            F::save_comment_usage(&F::load_assignment(&file)?, order)?;
+        }
+        Command::SetTemplate { file, template } => {
+            let mut asn = F::load_assignment(&file)?;
+            if let Some(t) = template.as_ref() {
+                F::load_template(t)?;
+            }
+            asn.set_latex_template(template);
+            F::save_assignment(&asn, &file)?;
+        }
+        Command::DefaultTemplate => println!("{}", latex::DEFAULT_TEMPLATE),
+        Command::SetEngine { file, engine } => {
+            let mut asn = F::load_assignment(&file)?;
+            asn.set_pdf_engine(engine);
+            F::save_assignment(&asn, &file)?;
+        }
+        Command::Summary { file } => {
+            println!("{}", summary(&F::load_assignment(&file)?));
+        }
+        Command::AddStudent { file, student } => {
+            let mut asn = F::load_assignment(&file)?;
+            if !student.number.is_empty() && asn.number_exists(&student.number) {
+                return Err(CliError::Invalid(format!(
+                    "a student numbered '{}' has already been added",
+                    student.number
+                )));
+            }
+            asn.add_student(student);
+            F::save_assignment(&asn, &file)?;
+        }
+        Command::ImportRoster {
+            file,
+            roster,
+            columns,
+        } => {
+            let mut asn = F::load_assignment(&file)?;
+            let text = F::load_roster(&roster)?;
+            let report = roster::import(&mut asn, &text, &columns).map_err(CliError::Invalid)?;
+            println!("{}", report.describe());
+            F::save_assignment(&asn, &file)?;
+        }
+        Command::Help => println!("{}", USAGE),
+    }
+    Ok(())
+}
+
+// Describes the question parts that have not been graded for the student,
+// or for every student, since their grade sheets will not have real marks
+// for them.
+fn ungraded_warning(assignment: &Assignment, student: Option<u64>) -> Option<String> {
+    let (ungraded, total) = match student {
+        Some(id) => {
+            let ungraded = assignment
+                .get_questions()
+                .iter()
+                .filter(|q| !assignment.is_graded(id, q))
+                .count() as u32;
+            (ungraded, assignment.num_questions())
+        }
+        None => {
+            let total = assignment.num_students() * assignment.num_questions();
+            (total - assignment.total_graded(), total)
+        }
+    };
+    if ungraded == 0 {
+        return None;
+    }
+    Some(format!(
+        "{} of {} question parts have not been graded",
+        ungraded, total
+    ))
+}
+
+// Finds the id of the student with the given student number, or failing that
+// the given name. A name shared by more than one student is an error, since
+// the number is needed to tell them apart. Students without a number can
+// only be found by name.
+fn find_student(assignment: &Assignment, key: &str) -> Result<u64, CliError> {
+    let students = assignment.get_students();
+    if !key.is_empty() {
+        if let Some(s) = students.iter().find(|s| s.number == key) {
+            return Ok(s.id);
+        }
+    }
+    let named: Vec<&Student> = students.iter().filter(|s| s.name == key).collect();
+    match named.as_slice() {
+        [s] => Ok(s.id),
+        [] => Err(CliError::Invalid(format!("no student named '{}'", key))),
+        _ => Err(CliError::Invalid(format!(
+            "more than one student is named '{}', use their student number",
+            key
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::{Question, Scaling};
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn it_parses_export_commands() {
+        assert_eq!(
+            parse(&args(&["export-latex", "a.emark"])),
+            Ok(Command::ExportSheets {
+                file: "a.emark".to_string(),
+                format: "latex".to_string(),
+                student: None
+            })
+        );
+        assert_eq!(
+            parse(&args(&["export-latex", "a.emark", "Issac Newton"])),
+            Ok(Command::ExportSheets {
+                file: "a.emark".to_string(),
+                format: "latex".to_string(),
+                student: Some("Issac Newton".to_string())
+            })
+        );
+        assert_eq!(
+            parse(&args(&["export-sheets", "a.emark", "HTML", "001234"])),
+            Ok(Command::ExportSheets {
+                file: "a.emark".to_string(),
+                format: "html".to_string(),
+                student: Some("001234".to_string())
+            })
+        );
+        assert_eq!(
+            parse(&args(&["export-sheets", "a.emark", "markdown"])),
+            Ok(Command::ExportSheets {
+                file: "a.emark".to_string(),
+                format: "markdown".to_string(),
+                student: None
+            })
+        );
+        assert_eq!(
+            parse(&args(&["export-csv", "a.emark", "--long"])),
+            Ok(Command::ExportCsv {
+                file: "a.emark".to_string(),
+                long: true
+            })
+        );
+        assert_eq!(
+            parse(&args(&["export-lms", "a.emark", "Canvas"])),
+            Ok(Command::ExportLms {
+                file: "a.emark".to_string(),
+                lms: Lms::Canvas
+            })
+        );
+        assert_eq!(
+            parse(&args(&["export-stats", "a.emark", "--latex"])),
+            Ok(Command::ExportStats {
+                file: "a.emark".to_string(),
+                latex: true
+            })
+        );
+        assert_eq!(
+            parse(&args(&["export-usage", "a.emark", "Students"])),
+            Ok(Command::ExportUsage {
+                file: "a.emark".to_string(),
+                order: UsageOrder::Students
+            })
+        );
+        assert_eq!(
+            parse(&args(&["export-usage", "a.emark"])),
+            Ok(Command::ExportUsage {
+                file: "a.emark".to_string(),
+                order: UsageOrder::MarksLost
+            })
+        );
+    }
+
+    #[test]
+    fn it_parses_other_commands() {
+        assert_eq!(
+            parse(&args(&["summary", "a.emark"])),
+            Ok(Command::Summary {
+                file: "a.emark".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&args(&["add-student", "a.emark", "Marie Currie"])),
+            Ok(Command::AddStudent {
+                file: "a.emark".to_string(),
+                student: Student::new("Marie Currie")
+            })
+        );
+        let mut currie = Student::new("Marie Currie");
+        currie.number = "001234".to_string();
+        currie.email = "mcurrie@uni.ca".to_string();
+        assert_eq!(
+            parse(&args(&[
+                "add-student",
+                "a.emark",
+                "Marie Currie",
+                "001234",
+                "mcurrie@uni.ca"
+            ])),
+            Ok(Command::AddStudent {
+                file: "a.emark".to_string(),
+                student: currie
+            })
+        );
+        assert_eq!(
+            parse(&args(&[
+                "import-roster",
+                "a.emark",
+                "roster.csv",
+                "--name",
+                "Full Name",
+                "--section",
+                ""
+            ])),
+            Ok(Command::ImportRoster {
+                file: "a.emark".to_string(),
+                roster: "roster.csv".to_string(),
+                columns: RosterColumns {
+                    name: "Full Name".to_string(),
+                    section: String::new(),
+                    ..RosterColumns::default()
+                }
+            })
+        );
+        assert_eq!(parse(&args(&["--help"])), Ok(Command::Help));
+        assert_eq!(
+            parse(&args(&["set-template", "a.emark", "faculty.tex"])),
+            Ok(Command::SetTemplate {
+                file: "a.emark".to_string(),
+                template: Some("faculty.tex".to_string())
+            })
+        );
+        assert_eq!(
+            parse(&args(&["set-engine", "a.emark", "XeLaTeX"])),
+            Ok(Command::SetEngine {
+                file: "a.emark".to_string(),
+                engine: Some(LatexEngine::Xelatex)
+            })
+        );
+        assert!(parse(&args(&["set-engine", "a.emark", "lualatex"])).is_err());
+        assert_eq!(
+            parse(&args(&["default-template"])),
+            Ok(Command::DefaultTemplate)
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_arguments() {
+        assert!(parse(&args(&[])).is_err());
+        assert!(parse(&args(&["summary"])).is_err());
+        assert!(parse(&args(&["export-csv", "a.emark", "--wide"])).is_err());
+        assert!(parse(&args(&["export-lms", "a.emark", "d2l"])).is_err());
+        assert!(parse(&args(&["export-sheets", "a.emark", "pdf"])).is_err());
+        assert!(parse(&args(&["export-sheets", "a.emark"])).is_err());
+        assert!(parse(&args(&["export-usage", "a.emark", "impact"])).is_err());
+        assert!(parse(&args(&["grade", "a.emark"])).is_err());
+        assert!(parse(&args(&["import-roster", "a.emark", "r.csv", "--name"])).is_err());
+        assert!(parse(&args(&["import-roster", "a.emark", "r.csv", "--id", "ID"])).is_err());
+    }
+
+    #[test]
+    fn it_summarizes_an_assignment() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q.num, q.part, q.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        asn.add_comment(newton, &q, 1.5, "Try harder".to_string());
+
+        assert_eq!(
+            summary(&asn),
+            "CS 1000 - Assignment 5\n1 students, 1 question parts, out of 5\nIssac Newton\t3.5/5"
+        );
+        asn.set_scaling(vec![Scaling::OutOf(100)]);
+        assert_eq!(
+            summary(&asn),
+            "CS 1000 - Assignment 5\n1 students, 1 question parts, out of 100\nIssac Newton\t70/100"
+        );
+    }
+
+    #[test]
+    fn it_warns_about_exporting_ungraded_parts() {
+        let q1 = Question::new(1, 1, 5);
+        let q2 = Question::new(1, 2, 5);
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        let currie = asn.add_student(Student::new("Marie Currie"));
+        asn.add_comment(newton, &q1, 1.5, "Try harder".to_string());
+        asn.set_graded(newton, &q2, true);
+
+        assert_eq!(ungraded_warning(&asn, Some(newton)), None);
+        assert_eq!(
+            ungraded_warning(&asn, Some(currie)),
+            Some("2 of 2 question parts have not been graded".to_string())
+        );
+        assert_eq!(
+            ungraded_warning(&asn, None),
+            Some("2 of 4 question parts have not been graded".to_string())
+        );
+    }
+
+    #[test]
+    fn it_finds_students_by_number_or_unique_name() {
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        let mut currie = Student::new("Marie Currie");
+        currie.number = "001234".to_string();
+        let currie = asn.add_student(currie);
+        asn.add_student(Student::new("Marie Currie"));
+
+        assert_eq!(find_student(&asn, "Issac Newton").unwrap(), newton);
+        assert_eq!(find_student(&asn, "001234").unwrap(), currie);
+        assert!(find_student(&asn, "Marie Currie").is_err());
+        assert!(find_student(&asn, "Albert Einstein").is_err());
+        assert!(find_student(&asn, "").is_err());
+    }
+}
@@ -1,5 +1,9 @@
-use crate::data::{Comment, Question};
+use crate::data::late::{self, LatePolicy};
+use crate::data::latex::LatexEngine;
+use crate::data::{scaling, Action, BonusPolicy, Comment, History, Question, Scaling, Student};
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
 /// An assignment for a given course. Collects all the students, questions, and
 /// comments together. Stores all question parts with their comments so that
@@ -11,12 +15,39 @@ pub struct Assignment {
     /// The course the assignment is for.
     pub course: String,
     // All of the students being graded for this assignment.
-    students: Vec<String>,
+    students: Vec<Student>,
+    // The next unique id to give to an added student.
+    next_student_id: u64,
     // Sequential list of question parts, along with their comments. They are
     // sorted by question number and then question part.
     questions: Vec<QuestAndComs>,
     // The next unique id to give to an added comment.
     next_id: u64,
+    // The grading actions that can be undone and redone.
+    #[serde(default)]
+    history: History,
+    // Whether bonus marks can take a student's total over the out of. It is
+    // also used for any question part without a policy of its own.
+    #[serde(default)]
+    bonus_policy: BonusPolicy,
+    // The steps applied in order to the students' totals when they are
+    // exported, such as a curve.
+    #[serde(default)]
+    scaling: Vec<Scaling>,
+    // When the assignment is due. None if late submissions are not tracked.
+    #[serde(default)]
+    due_date: Option<NaiveDateTime>,
+    // How marks are taken off students' totals for late submissions.
+    #[serde(default)]
+    late_policy: LatePolicy,
+    // The path of the template LaTeX grade sheets are laid out with. None
+    // uses the default layout.
+    #[serde(default)]
+    latex_template: Option<String>,
+    // The engine LaTeX grade sheets are compiled to PDF with after they are
+    // written. None leaves them as .tex files.
+    #[serde(default)]
+    pdf_engine: Option<LatexEngine>,
 }
 
 impl Assignment {
@@ -25,39 +56,138 @@ impl Assignment {
             title,
             course,
             students: Vec::new(),
+            next_student_id: 0,
             questions: Vec::new(),
             next_id: 0,
+            history: History::default(),
+            bonus_policy: BonusPolicy::default(),
+            scaling: Vec::new(),
+            due_date: None,
+            late_policy: LatePolicy::default(),
+            latex_template: None,
+            pdf_engine: None,
         }
     }
 
     // Students //////////////////////////////////////////////////////////////
-    /// The number of students that have been added.
+    /// The number of students being graded, not counting withdrawn students.
     pub fn num_students(&self) -> u32 {
-        self.students.len() as u32
+        self.active_students().count() as u32
     }
 
-    /// True if a student has been added.
-    pub fn student_exists(&self, student: &str) -> bool {
-        self.students.iter().any(|s| s == student)
+    /// True if a student with the given student number has been added,
+    /// including withdrawn students.
+    pub fn number_exists(&self, number: &str) -> bool {
+        self.students.iter().any(|s| s.number == number)
     }
 
-    /// Get the name of the student at the given index.
+    /// True if a student with the given name has been added, including
+    /// withdrawn students.
+    pub fn name_exists(&self, name: &str) -> bool {
+        self.students.iter().any(|s| s.name == name)
+    }
+
+    /// Get the student at the given index, not counting withdrawn students.
     /// Panics if the given index is out of range for the number of students.
-    pub fn get_student_at(&self, idx: u32) -> String {
-        self.students[idx as usize].clone()
+    pub fn get_student_at(&self, idx: u32) -> Student {
+        self.active_students().nth(idx as usize).unwrap().clone()
     }
 
-    /// Get a vector of all the students that have been added to the assignment.
-    pub fn get_students(&self) -> Vec<String> {
-        self.students.iter().map(|s| s.clone()).collect()
+    /// Get the student with the given id.
+    /// Panics if the student does not exist.
+    pub fn get_student(&self, student: u64) -> Student {
+        self.students
+            .iter()
+            .find(|s| s.id == student)
+            .unwrap()
+            .clone()
     }
 
-    /// Add a new student to the assignment.
+    /// Get a vector of all the students being graded, leaving out withdrawn
+    /// students.
+    pub fn get_students(&self) -> Vec<Student> {
+        self.active_students().cloned().collect()
+    }
+
+    /// Get a vector of the students that have withdrawn.
+    pub fn get_withdrawn_students(&self) -> Vec<Student> {
+        self.students
+            .iter()
+            .filter(|s| s.withdrawn)
+            .cloned()
+            .collect()
+    }
+
+    /// Add a new student to the assignment and return the id given to them.
     /// The students index will be the num_students before adding.
-    /// Panics if the student has already been added.
-    pub fn add_student(&mut self, student: &str) {
-        assert!(!self.student_exists(student));
-        self.students.push(student.to_string());
+    /// Panics if the student has a student number that has already been added.
+    pub fn add_student(&mut self, mut student: Student) -> u64 {
+        assert!(student.number.is_empty() || !self.number_exists(&student.number));
+        student.id = self.next_student_id;
+        self.next_student_id += 1;
+        self.students.push(student);
+        self.next_student_id - 1
+    }
+
+    /// Changes the name of a student. Their comments and marks stay with them.
+    /// Panics if the student does not exist.
+    pub fn rename_student(&mut self, student: u64, name: &str) {
+        self.get_student_mut(student).name = name.to_string();
+    }
+
+    /// Withdraws a student, leaving them out of grading and exports while
+    /// keeping their comments and marks.
+    /// Panics if the student does not exist.
+    pub fn withdraw_student(&mut self, student: u64) {
+        self.get_student_mut(student).withdrawn = true;
+    }
+
+    /// Brings back a withdrawn student with the marks they had.
+    /// Panics if the student does not exist.
+    pub fn reinstate_student(&mut self, student: u64) {
+        self.get_student_mut(student).withdrawn = false;
+    }
+
+    /// Sets when a student submitted the assignment, or None if it is not
+    /// known.
+    /// Panics if the student does not exist.
+    pub fn set_submitted(&mut self, student: u64, submitted: Option<NaiveDateTime>) {
+        self.get_student_mut(student).submitted = submitted;
+    }
+
+    /// Gives a student their own due date, or None to take away their
+    /// extension.
+    /// Panics if the student does not exist.
+    pub fn set_extension(&mut self, student: u64, extension: Option<NaiveDateTime>) {
+        self.get_student_mut(student).extension = extension;
+    }
+
+    /// Deletes a student and takes them off all of their comments. Comments
+    /// left without any students are deleted too.
+    /// Undo history is cleared, since it may refer to the removed student.
+    /// Panics if the student does not exist.
+    pub fn remove_student(&mut self, student: u64) {
+        let idx = self.students.iter().position(|s| s.id == student).unwrap();
+        self.students.remove(idx);
+        for qc in self.questions.iter_mut() {
+            for com in qc.comments.iter_mut() {
+                com.remove_student(student);
+            }
+            qc.comments.retain(|c| !c.empty());
+            qc.graded.remove(&student);
+        }
+        self.history.clear();
+    }
+
+    // Helper to iterate over the students that have not withdrawn.
+    fn active_students(&self) -> impl Iterator<Item = &Student> {
+        self.students.iter().filter(|s| !s.withdrawn)
+    }
+
+    // Helper to get a mutable reference to a student.
+    // Panics if the student does not exist.
+    fn get_student_mut(&mut self, student: u64) -> &mut Student {
+        self.students.iter_mut().find(|s| s.id == student).unwrap()
     }
 
     // Questions /////////////////////////////////////////////////////////////
@@ -74,7 +204,7 @@ impl Assignment {
     pub fn question_exists(&self, question: &Question) -> bool {
         self.questions
             .iter()
-            .any(|qc| qc.question.num == question.num && qc.question.part == question.part)
+            .any(|qc| qc.question.same_part(question))
     }
 
     /// Gets the question data at the given index.
@@ -107,93 +237,244 @@ impl Assignment {
         self.questions.push(QuestAndComs {
             question: q,
             comments: Vec::new(),
+            bonus_policy: None,
+            graded: BTreeSet::new(),
         });
     }
 
+    /// Adds a new question part in order of its number and part, so that a
+    /// forgotten part can be added after grading has started.
+    /// Panics if the question has already been added.
+    pub fn insert_question(&mut self, num: u32, part: u32, out_of: u32) {
+        self.add_question(num, part, out_of);
+        self.sort_questions();
+    }
+
+    /// Removes a question part along with all of its comments.
+    /// Undo history is cleared, since it may refer to the removed comments.
+    /// Panics if the question does not exist.
+    pub fn remove_question(&mut self, question: &Question) {
+        let idx = self.question_index(question);
+        self.questions.remove(idx);
+        self.history.clear();
+    }
+
+    /// Changes the number of marks a question part is out of. Its comments
+    /// stay attached.
+    /// Panics if the question does not exist.
+    pub fn set_out_of(&mut self, question: &Question, out_of: u32) {
+        let idx = self.question_index(question);
+        self.questions[idx].question.out_of = out_of;
+    }
+
+    /// Gives a question part a new number and part, keeping its comments, and
+    /// moves it to its place in the order.
+    /// Undo history is cleared, since it refers to questions by number.
+    /// Panics if the question does not exist or another question already
+    /// has the new number and part.
+    pub fn renumber_question(&mut self, question: &Question, num: u32, part: u32) {
+        let idx = self.question_index(question);
+        let renumbered = Question::new(num, part, question.out_of);
+        assert!(question.same_part(&renumbered) || !self.question_exists(&renumbered));
+
+        self.questions[idx].question.num = num;
+        self.questions[idx].question.part = part;
+        self.sort_questions();
+        self.history.clear();
+    }
+
+    /// The path of the template LaTeX grade sheets are laid out with, or None
+    /// for the default layout.
+    pub fn latex_template(&self) -> Option<&str> {
+        self.latex_template.as_deref()
+    }
+
+    /// Sets the path of the template for LaTeX grade sheets, or None to use
+    /// the default layout.
+    pub fn set_latex_template(&mut self, path: Option<String>) {
+        self.latex_template = path;
+    }
+
+    /// The engine LaTeX grade sheets are compiled to PDF with, or None if
+    /// they are not compiled.
+    pub fn pdf_engine(&self) -> Option<LatexEngine> {
+        self.pdf_engine
+    }
+
+    /// Sets the engine to compile LaTeX grade sheets to PDF with, or None to
+    /// stop compiling them.
+    pub fn set_pdf_engine(&mut self, engine: Option<LatexEngine>) {
+        self.pdf_engine = engine;
+    }
+
+    /// The bonus policy for the whole assignment's total.
+    pub fn bonus_policy(&self) -> BonusPolicy {
+        self.bonus_policy
+    }
+
+    /// Sets the bonus policy for the whole assignment's total. It also applies
+    /// to question parts that do not have a policy of their own.
+    pub fn set_bonus_policy(&mut self, policy: BonusPolicy) {
+        self.bonus_policy = policy;
+    }
+
+    /// The bonus policy that applies to a question part.
+    /// Panics if the question does not exist.
+    pub fn question_bonus_policy(&self, question: &Question) -> BonusPolicy {
+        let idx = self.question_index(question);
+        self.questions[idx]
+            .bonus_policy
+            .unwrap_or(self.bonus_policy)
+    }
+
+    /// Sets the bonus policy for a question part, or None to use the
+    /// assignment's policy.
+    /// Panics if the question does not exist.
+    pub fn set_question_bonus_policy(&mut self, question: &Question, policy: Option<BonusPolicy>) {
+        let idx = self.question_index(question);
+        self.questions[idx].bonus_policy = policy;
+    }
+
+    // Helper to find the index of a question part. Panics if it does not exist.
+    fn question_index(&self, question: &Question) -> usize {
+        self.questions
+            .iter()
+            .position(|qc| qc.question.same_part(question))
+            .unwrap()
+    }
+
+    // Helper to put the questions back in order of number and part.
+    fn sort_questions(&mut self) {
+        self.questions
+            .sort_by_key(|qc| (qc.question.num, qc.question.part));
+    }
+
     // Comments //////////////////////////////////////////////////////////////
-    /// Adds a new deduction and comment to the given question for a student.
-    pub fn add_comment(
-        &mut self,
-        student: &str,
-        question: &Question,
-        deduction: f32,
-        text: String,
-    ) {
-        let com = Comment::new(self.next_id, deduction, text, student.to_string());
+    /// Adds a new deduction and comment to the given question for a student,
+    /// which marks the student as graded on the question.
+    pub fn add_comment(&mut self, student: u64, question: &Question, deduction: f32, text: String) {
+        let com = Comment::new(self.next_id, deduction, text, student);
         self.next_id += 1;
 
+        self.history.record(Action::CommentCreated {
+            question: question.clone(),
+            comment: com.clone(),
+        });
         self.get_comments_mut(question).push(com);
+        self.set_graded(student, question, true);
     }
 
     /// Add a student to a comment for the given question.
     /// This represents
     /// adding the comment to a students grade sheet, but the assignment
     /// and comment data represent it the other way.
-    pub fn add_to_comment(&mut self, student: &str, question: &Question, id: u64) {
-        self.get_comments_mut(question)
-            .iter_mut()
-            .find(|c| c.id == id)
-            .unwrap()
-            .add_student(student.to_string());
+    pub fn add_to_comment(&mut self, student: u64, question: &Question, id: u64) {
+        self.get_comment_mut(question, id).add_student(student);
+        self.set_graded(student, question, true);
+        self.history.record(Action::StudentAdded {
+            question: question.clone(),
+            id,
+            student,
+        });
     }
 
     /// Remove a student from the comment for the given question.
     /// If a comment has no students left it is deleted. This is to keep
     /// the assignment uncluttered with useless or mistaken comments. Though
     /// there may not always be intuitive.
-    pub fn remove_from_comment(&mut self, student: &str, question: &Question, id: u64) {
-        let (id, empty) = {
-            let com = self
-                .get_comments_mut(question)
-                .iter_mut()
-                .find(|c| c.id == id)
-                .unwrap();
-
-            com.remove_student(student);
-            (com.id, com.empty())
-        };
-        if empty {
-            self.get_comments_mut(question).retain(|c| c.id != id);
-        }
+    pub fn remove_from_comment(&mut self, student: u64, question: &Question, id: u64) {
+        let comment = self.get_comment_mut(question, id).clone();
+        self.take_from_comment(student, question, id);
+        self.history.record(Action::StudentRemoved {
+            question: question.clone(),
+            student,
+            comment,
+        });
     }
 
     /// Adjusts a comment to have the given deduction and text for the
     /// a question.
     pub fn edit_comment(&mut self, question: &Question, id: u64, deduction: f32, text: String) {
-        let mut com = self
-            .get_comments_mut(question)
-            .iter_mut()
-            .find(|c| c.id == id)
-            .unwrap();
+        let com = self.get_comment_mut(question, id);
+        let old = (com.deduction, com.text.clone());
         com.deduction = deduction;
-        com.text = text;
+        com.text = text.clone();
+        self.history.record(Action::CommentEdited {
+            question: question.clone(),
+            id,
+            old,
+            new: (deduction, text),
+        });
+    }
+
+    /// Sets whether a comment's text is put in LaTeX grade sheets as it is,
+    /// rather than escaped.
+    /// Panics if the question or comment does not exist.
+    pub fn set_comment_raw_latex(&mut self, question: &Question, id: u64, raw: bool) {
+        self.get_comment_mut(question, id).raw_latex = raw;
     }
 
     /// Get a vector of all a student's comments for the given question.
-    pub fn students_comments_for(&self, student: &str, question: &Question) -> Vec<Comment> {
+    pub fn students_comments_for(&self, student: u64, question: &Question) -> Vec<Comment> {
         self.get_comments(question)
             .iter()
             .filter(|c| c.has_student(student))
-            .map(|c| c.clone())
+            .cloned()
             .collect()
     }
 
     /// Get a vector of all comments for the given question that have not
     /// been associated with a student.
-    pub fn unused_comments_for(&self, student: &str, question: &Question) -> Vec<Comment> {
+    pub fn unused_comments_for(&self, student: u64, question: &Question) -> Vec<Comment> {
         self.get_comments(question)
             .iter()
             .filter(|c| !c.has_student(student))
-            .map(|c| c.clone())
+            .cloned()
             .collect()
     }
 
+    /// Get a vector of every comment for the given question, in the order
+    /// they were created.
+    pub fn comments_for(&self, question: &Question) -> Vec<Comment> {
+        self.get_comments(question).to_vec()
+    }
+
+    // Helper to remove a student from a comment without recording it, and
+    // delete the comment if no students are left.
+    fn take_from_comment(&mut self, student: u64, question: &Question, id: u64) {
+        let com = self.get_comment_mut(question, id);
+        com.remove_student(student);
+        if com.empty() {
+            self.get_comments_mut(question).retain(|c| c.id != id);
+        }
+    }
+
+    // Helper to put a deleted comment back in its original place. Comments
+    // are kept in the order of their ids, since ids are given out in order.
+    fn restore_comment(&mut self, question: &Question, comment: Comment) {
+        let comments = self.get_comments_mut(question);
+        let idx = comments
+            .iter()
+            .position(|c| c.id > comment.id)
+            .unwrap_or(comments.len());
+        comments.insert(idx, comment);
+    }
+
+    // Helper to get a mutable reference to a comment on a question.
+    // Panics if the comment does not exist.
+    fn get_comment_mut(&mut self, question: &Question, id: u64) -> &mut Comment {
+        self.get_comments_mut(question)
+            .iter_mut()
+            .find(|c| c.id == id)
+            .unwrap()
+    }
+
     // Helper to get a mutable reference to the vector of comments.
     fn get_comments_mut(&mut self, question: &Question) -> &mut Vec<Comment> {
         &mut self
             .questions
             .iter_mut()
-            .find(|qc| &qc.question == question)
+            .find(|qc| qc.question.same_part(question))
             .unwrap()
             .comments
     }
@@ -203,11 +484,160 @@ impl Assignment {
         &self
             .questions
             .iter()
-            .find(|qc| &qc.question == question)
+            .find(|qc| qc.question.same_part(question))
             .unwrap()
             .comments
     }
 
+    // Progress //////////////////////////////////////////////////////////////
+    /// True if the student has been graded on the question. Giving a student
+    /// a comment marks them as graded, and a question without comments can be
+    /// marked as graded to give full marks.
+    pub fn is_graded(&self, student: u64, question: &Question) -> bool {
+        self.questions[self.question_index(question)]
+            .graded
+            .contains(&student)
+    }
+
+    /// Marks a student as graded, or not graded, on a question.
+    /// It is not recorded in the undo history, and undoing a comment does
+    /// not change whether the student has been graded.
+    /// Panics if the question does not exist.
+    pub fn set_graded(&mut self, student: u64, question: &Question, graded: bool) {
+        let idx = self.question_index(question);
+        if graded {
+            self.questions[idx].graded.insert(student);
+        } else {
+            self.questions[idx].graded.remove(&student);
+        }
+    }
+
+    /// The number of students being graded that have been graded on the
+    /// question.
+    pub fn num_graded(&self, question: &Question) -> u32 {
+        self.active_students()
+            .filter(|s| self.is_graded(s.id, question))
+            .count() as u32
+    }
+
+    /// The number of student and question part pairs that have been graded,
+    /// out of num_students * num_questions.
+    pub fn total_graded(&self) -> u32 {
+        self.questions
+            .iter()
+            .map(|qc| self.num_graded(&qc.question))
+            .sum()
+    }
+
+    /// The next student and question part that has not been graded, after the
+    /// given ones, wrapping around to the start. Goes through every question
+    /// for a student before the next student, or every student for a question
+    /// before the next question if by_question is true.
+    /// Returns None if everything has been graded.
+    pub fn next_ungraded(
+        &self,
+        student: u64,
+        question: &Question,
+        by_question: bool,
+    ) -> Option<(u64, Question)> {
+        let students = self.get_students();
+        let questions = self.get_questions();
+        let pairs: Vec<(u64, Question)> = if by_question {
+            questions
+                .iter()
+                .flat_map(|q| students.iter().map(move |s| (s.id, q.clone())))
+                .collect()
+        } else {
+            students
+                .iter()
+                .flat_map(|s| questions.iter().map(move |q| (s.id, q.clone())))
+                .collect()
+        };
+
+        let start = pairs
+            .iter()
+            .position(|(s, q)| *s == student && q.same_part(question))
+            .map_or(0, |i| i + 1);
+        pairs
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(pairs.len())
+            .find(|(s, q)| !self.is_graded(*s, q))
+            .cloned()
+    }
+
+    // History ///////////////////////////////////////////////////////////////
+    /// Reverses the most recent grading action.
+    /// Returns the action that was undone, or None if there was nothing to undo.
+    pub fn undo(&mut self) -> Option<Action> {
+        let action = self.history.undo()?;
+        match &action {
+            Action::CommentCreated { question, comment } => {
+                self.get_comments_mut(question)
+                    .retain(|c| c.id != comment.id);
+            }
+            Action::StudentAdded {
+                question,
+                id,
+                student,
+            } => self.take_from_comment(*student, question, *id),
+            Action::CommentEdited {
+                question, id, old, ..
+            } => {
+                let com = self.get_comment_mut(question, *id);
+                com.deduction = old.0;
+                com.text = old.1.clone();
+            }
+            Action::StudentRemoved {
+                question,
+                student,
+                comment,
+            } => {
+                let exists = self
+                    .get_comments(question)
+                    .iter()
+                    .any(|c| c.id == comment.id);
+                if exists {
+                    self.get_comment_mut(question, comment.id)
+                        .add_student(*student);
+                } else {
+                    self.restore_comment(question, comment.clone());
+                }
+            }
+        }
+        Some(action)
+    }
+
+    /// Performs the most recently undone grading action again.
+    /// Returns the action that was redone, or None if there was nothing to redo.
+    pub fn redo(&mut self) -> Option<Action> {
+        let action = self.history.redo()?;
+        match &action {
+            Action::CommentCreated { question, comment } => {
+                self.restore_comment(question, comment.clone());
+            }
+            Action::StudentAdded {
+                question,
+                id,
+                student,
+            } => self.get_comment_mut(question, *id).add_student(*student),
+            Action::CommentEdited {
+                question, id, new, ..
+            } => {
+                let com = self.get_comment_mut(question, *id);
+                com.deduction = new.0;
+                com.text = new.1.clone();
+            }
+            Action::StudentRemoved {
+                question,
+                student,
+                comment,
+            } => self.take_from_comment(*student, question, comment.id),
+        }
+        Some(action)
+    }
+
     // Marks /////////////////////////////////////////////////////////////////
     /// Returns the total number of marks the assignment is out of.
     pub fn out_of(&self) -> u32 {
@@ -217,22 +647,120 @@ impl Assignment {
     }
 
     /// Returns the total number of marks a student has obtained
-    /// on the assignment.
-    pub fn students_total(&self, student: &str) -> f32 {
-        self.questions.iter().fold(0.0, |acc, qc| {
+    /// on the assignment, less any late penalty. It is only over the out of
+    /// if the assignment's bonus policy allows it.
+    pub fn students_total(&self, student: u64) -> f32 {
+        self.students_marks_total(student) - self.students_late_penalty(student)
+    }
+
+    /// Returns the total of a student's marks on every question before any
+    /// late penalty. It is only over the out of if the assignment's bonus
+    /// policy allows it.
+    pub fn students_marks_total(&self, student: u64) -> f32 {
+        let total = self.questions.iter().fold(0.0, |acc, qc| {
             acc + self.students_mark_for(student, &qc.question)
-        })
+        });
+        self.bonus_policy.limit(total, self.out_of() as f32)
+    }
+
+    /// When the assignment is due, or None if late submissions are not
+    /// tracked.
+    pub fn due_date(&self) -> Option<NaiveDateTime> {
+        self.due_date
+    }
+
+    /// Sets when the assignment is due, or None to stop tracking late
+    /// submissions.
+    pub fn set_due_date(&mut self, due_date: Option<NaiveDateTime>) {
+        self.due_date = due_date;
+    }
+
+    /// How marks are taken off for late submissions.
+    pub fn late_policy(&self) -> LatePolicy {
+        self.late_policy
+    }
+
+    /// Sets how marks are taken off for late submissions.
+    pub fn set_late_policy(&mut self, policy: LatePolicy) {
+        self.late_policy = policy;
+    }
+
+    /// When a student's submission is due, their extension if they have one.
+    /// Panics if the student does not exist.
+    pub fn students_due_date(&self, student: u64) -> Option<NaiveDateTime> {
+        self.get_student(student).extension.or(self.due_date)
+    }
+
+    /// The number of days, or parts of days, a student's submission was late.
+    /// Zero if it was on time, or if there is no due date or submission time.
+    /// Panics if the student does not exist.
+    pub fn students_days_late(&self, student: u64) -> u32 {
+        match (
+            self.get_student(student).submitted,
+            self.students_due_date(student),
+        ) {
+            (Some(submitted), Some(due)) => late::days_late(submitted, due),
+            _ => 0,
+        }
+    }
+
+    /// The marks taken off a student's total for submitting late.
+    /// Panics if the student does not exist.
+    pub fn students_late_penalty(&self, student: u64) -> f32 {
+        self.late_policy.penalty(
+            self.students_days_late(student),
+            self.students_marks_total(student),
+            self.out_of(),
+        )
+    }
+
+    /// The steps applied in order to the students' totals when they are
+    /// exported.
+    pub fn scaling(&self) -> &[Scaling] {
+        &self.scaling
+    }
+
+    /// Replaces the scaling steps. An empty list turns scaling off.
+    pub fn set_scaling(&mut self, scaling: Vec<Scaling>) {
+        self.scaling = scaling;
+    }
+
+    /// Returns what the scaled totals are out of.
+    pub fn scaled_out_of(&self) -> u32 {
+        scaling::scale(&self.scaling, &[], self.out_of()).1
+    }
+
+    /// Returns the scaled total of every student being graded. Steps that
+    /// depend on the class, like scaling to a mean, only use these students.
+    pub fn scaled_totals(&self) -> BTreeMap<u64, f32> {
+        let students = self.get_students();
+        let totals: Vec<f32> = students.iter().map(|s| self.students_total(s.id)).collect();
+        let (scaled, _) = scaling::scale(&self.scaling, &totals, self.out_of());
+        students.iter().map(|s| s.id).zip(scaled).collect()
+    }
+
+    /// Returns a student's total after scaling. It is the same as their
+    /// total when there is no scaling. Withdrawn students are not scaled.
+    pub fn students_scaled_total(&self, student: u64) -> f32 {
+        match self.scaled_totals().get(&student) {
+            Some(total) => *total,
+            None => self.students_total(student),
+        }
     }
 
     /// Returns the number of marks a student obtained on the given question.
-    pub fn students_mark_for(&self, student: &str, question: &Question) -> f32 {
+    /// It is never below zero, and is only over what the question is out of
+    /// if the question's bonus policy allows it.
+    pub fn students_mark_for(&self, student: u64, question: &Question) -> f32 {
         let total = question.out_of as f32;
         let deducted = self
             .students_comments_for(student, question)
             .iter()
             .fold(0.0, |acc, c| acc + c.deduction);
 
-        let res = total - deducted;
+        let res = self
+            .question_bonus_policy(question)
+            .limit(total - deducted, total);
         if res > 0.0 {
             res
         } else {
@@ -247,50 +775,111 @@ impl Assignment {
 struct QuestAndComs {
     pub question: Question,
     pub comments: Vec<Comment>,
+    // Overrides the assignment's bonus policy for this question if set.
+    #[serde(default)]
+    pub bonus_policy: Option<BonusPolicy>,
+    // The ids of the students that have been graded on this question.
+    #[serde(default)]
+    pub graded: BTreeSet<u64>,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::data::late::LatePenalty;
+
+    const NEWTON: u64 = 0;
+    const EINSTEIN: u64 = 1;
+    const CURRIE: u64 = 2;
 
     fn make_test_asn() -> Assignment {
         Assignment::new("Assignment 5".to_string(), "CS 1000".to_string())
     }
 
     #[test]
-    fn it_adds_new_students_and_can_tell_they_exist() {
+    fn it_adds_new_students_with_unique_ids() {
         let mut asn = make_test_asn();
-        asn.add_student("Issac Newton");
-        asn.add_student("Albert Einstein");
-        asn.add_student("Marie Currie");
+        assert_eq!(asn.add_student(Student::new("Issac Newton")), NEWTON);
+        assert_eq!(asn.add_student(Student::new("Albert Einstein")), EINSTEIN);
+        assert_eq!(asn.add_student(Student::new("Marie Currie")), CURRIE);
         assert_eq!(asn.num_students(), 3);
-        assert!(asn.student_exists("Issac Newton"));
-        assert!(asn.student_exists("Albert Einstein"));
-        assert!(asn.student_exists("Marie Currie"));
+        assert_eq!(asn.get_student(EINSTEIN).name, "Albert Einstein");
     }
 
     #[test]
     fn it_gets_the_students_in_expected_order() {
-        let mut asn = make_test_asn();
-        asn.add_student("Issac Newton");
-        asn.add_student("Albert Einstein");
-        asn.add_student("Marie Currie");
-        assert_eq!(asn.get_student_at(0), "Issac Newton".to_string());
-        assert_eq!(asn.get_student_at(1), "Albert Einstein".to_string());
-        assert_eq!(asn.get_student_at(2), "Marie Currie".to_string());
+        let asn = asn_with_students_and_questions();
+        assert_eq!(asn.get_student_at(0).name, "Issac Newton");
+        assert_eq!(asn.get_student_at(1).name, "Albert Einstein");
+        assert_eq!(asn.get_student_at(2).name, "Marie Currie");
 
         let students = asn.get_students();
-        assert_eq!(students[0], "Issac Newton".to_string());
-        assert_eq!(students[1], "Albert Einstein".to_string());
-        assert_eq!(students[2], "Marie Currie".to_string());
+        assert_eq!(students[0].id, NEWTON);
+        assert_eq!(students[1].id, EINSTEIN);
+        assert_eq!(students[2].id, CURRIE);
+    }
+
+    #[test]
+    fn it_allows_students_with_the_same_name() {
+        let mut asn = make_test_asn();
+        let first = asn.add_student(Student::new("Issac Newton"));
+        let second = asn.add_student(Student::new("Issac Newton"));
+        assert_ne!(first, second);
+        assert_eq!(asn.num_students(), 2);
+    }
+
+    #[test]
+    fn it_renames_a_student_and_keeps_their_marks() {
+        let mut asn = asn_with_students_and_questions();
+        let q = asn.get_question_at(0);
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.rename_student(NEWTON, "Isaac Newton");
+
+        assert_eq!(asn.get_student_at(0).name, "Isaac Newton");
+        assert_eq!(asn.students_comments_for(NEWTON, &q).len(), 1);
+    }
+
+    #[test]
+    fn it_withdraws_and_reinstates_a_student_with_their_marks() {
+        let mut asn = asn_with_students_and_questions();
+        let q = asn.get_question_at(0);
+        asn.add_comment(EINSTEIN, &q, 3.0, "Amateurish work".to_string());
+        asn.withdraw_student(EINSTEIN);
+
+        assert_eq!(asn.num_students(), 2);
+        assert_eq!(asn.get_student_at(1).id, CURRIE);
+        assert_eq!(asn.get_withdrawn_students()[0].id, EINSTEIN);
+        assert!(asn.name_exists("Albert Einstein"));
+
+        asn.reinstate_student(EINSTEIN);
+        assert_eq!(asn.get_student_at(1).id, EINSTEIN);
+        assert_eq!(asn.students_mark_for(EINSTEIN, &q), 2.0);
+    }
+
+    #[test]
+    fn it_removes_a_student_from_their_comments() {
+        let mut asn = asn_with_students_and_questions();
+        let q = asn.get_question_at(0);
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_to_comment(CURRIE, &q, 0);
+        asn.add_comment(NEWTON, &q, 1.0, "Try harder".to_string());
+        asn.remove_student(NEWTON);
+
+        assert_eq!(asn.num_students(), 2);
+        assert_eq!(asn.get_student_at(0).id, EINSTEIN);
+        assert_eq!(asn.unused_comments_for(EINSTEIN, &q).len(), 1);
+        assert_eq!(asn.students_comments_for(CURRIE, &q).len(), 1);
+        assert!(asn.undo().is_none());
     }
 
     #[test]
     #[should_panic]
-    fn it_panics_if_an_existing_student_is_added() {
+    fn it_panics_if_an_existing_student_number_is_added() {
         let mut asn = make_test_asn();
-        asn.add_student("Issac Newton");
-        asn.add_student("Issac Newton");
+        let mut student = Student::new("Issac Newton");
+        student.number = "001234".to_string();
+        asn.add_student(student.clone());
+        asn.add_student(student);
     }
 
     #[test]
@@ -333,14 +922,81 @@ mod test {
         asn.add_question(1, 1, 10);
     }
 
+    #[test]
+    fn it_inserts_questions_in_order() {
+        let mut asn = make_test_asn();
+        asn.add_question(1, 1, 5);
+        asn.add_question(2, 1, 10);
+        asn.insert_question(1, 2, 5);
+        asn.insert_question(3, 1, 2);
+        asn.insert_question(0, 1, 1);
+
+        let labels: Vec<(u32, u32)> = asn
+            .get_questions()
+            .iter()
+            .map(|q| (q.num, q.part))
+            .collect();
+        assert_eq!(labels, vec![(0, 1), (1, 1), (1, 2), (2, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn it_removes_a_question_and_its_comments() {
+        let q = Question::new(1, 2, 5);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.remove_question(&q);
+
+        assert!(!asn.question_exists(&q));
+        assert_eq!(asn.num_questions(), 2);
+        assert_eq!(asn.out_of(), 15);
+        assert_eq!(asn.students_total(NEWTON), 15.0);
+        assert_eq!(asn.undo(), None);
+    }
+
+    #[test]
+    fn it_changes_what_a_question_is_out_of_and_keeps_comments() {
+        let q = Question::new(2, 1, 10);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.set_out_of(&q, 12);
+
+        let updated = asn.get_question_at(2);
+        assert_eq!(updated, Question::new(2, 1, 12));
+        assert_eq!(asn.students_mark_for(NEWTON, &updated), 9.0);
+        assert_eq!(asn.out_of(), 22);
+        // Undo still finds the comment after the change.
+        assert!(asn.undo().is_some());
+        assert_eq!(asn.students_comments_for(NEWTON, &updated).len(), 0);
+    }
+
+    #[test]
+    fn it_renumbers_a_question_and_keeps_comments() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.renumber_question(&q, 2, 2);
+
+        assert_eq!(asn.get_question_at(2), Question::new(2, 2, 5));
+        assert_eq!(asn.get_question_at(0), Question::new(1, 2, 5));
+        let coms = asn.students_comments_for(NEWTON, &Question::new(2, 2, 5));
+        assert_eq!(coms[0].text, "Amateurish work".to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_panics_if_a_question_is_renumbered_onto_another() {
+        let mut asn = asn_with_students_and_questions();
+        asn.renumber_question(&Question::new(1, 1, 5), 2, 1);
+    }
+
     fn asn_with_students_and_questions() -> Assignment {
         let mut asn = make_test_asn();
         asn.add_question(1, 1, 5);
         asn.add_question(1, 2, 5);
         asn.add_question(2, 1, 10);
-        asn.add_student("Issac Newton");
-        asn.add_student("Albert Einstein");
-        asn.add_student("Marie Currie");
+        asn.add_student(Student::new("Issac Newton"));
+        asn.add_student(Student::new("Albert Einstein"));
+        asn.add_student(Student::new("Marie Currie"));
         asn
     }
 
@@ -348,9 +1004,9 @@ mod test {
     fn it_adds_new_comment() {
         let q = Question::new(1, 1, 5);
         let mut asn = asn_with_students_and_questions();
-        asn.add_comment("Issac Newton", &q, 3.0, "Amateurish work".to_string());
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
 
-        let comments = asn.students_comments_for("Issac Newton", &q);
+        let comments = asn.students_comments_for(NEWTON, &q);
         assert_eq!(comments[0].id, 0);
         assert_eq!(comments[0].deduction, 3.0);
         assert_eq!(comments[0].text, "Amateurish work".to_string());
@@ -360,10 +1016,10 @@ mod test {
     fn it_adds_student_to_a_comment() {
         let q = Question::new(1, 1, 5);
         let mut asn = asn_with_students_and_questions();
-        asn.add_comment("Issac Newton", &q, 3.0, "Amateurish work".to_string());
-        asn.add_to_comment("Marie Currie", &q, 0);
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_to_comment(CURRIE, &q, 0);
 
-        let comments = asn.students_comments_for("Marie Currie", &q);
+        let comments = asn.students_comments_for(CURRIE, &q);
         assert_eq!(comments[0].id, 0);
         assert_eq!(comments[0].deduction, 3.0);
         assert_eq!(comments[0].text, "Amateurish work".to_string());
@@ -373,18 +1029,18 @@ mod test {
     fn it_removes_student_from_a_comment() {
         let q = Question::new(1, 1, 5);
         let mut asn = asn_with_students_and_questions();
-        asn.add_comment("Issac Newton", &q, 3.0, "Amateurish work".to_string());
-        asn.add_to_comment("Marie Currie", &q, 0);
-        asn.remove_from_comment("Issac Newton", &q, 0);
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_to_comment(CURRIE, &q, 0);
+        asn.remove_from_comment(NEWTON, &q, 0);
 
-        let comments = asn.students_comments_for("Issac Newton", &q);
+        let comments = asn.students_comments_for(NEWTON, &q);
         assert_eq!(comments.len(), 0);
-        let coms_mc = asn.students_comments_for("Marie Currie", &q);
+        let coms_mc = asn.students_comments_for(CURRIE, &q);
         assert_eq!(coms_mc.len(), 1);
 
         // now the comment should be removed completely
-        asn.remove_from_comment("Marie Currie", &q, 0);
-        let coms_mc = asn.students_comments_for("Marie Currie", &q);
+        asn.remove_from_comment(CURRIE, &q, 0);
+        let coms_mc = asn.students_comments_for(CURRIE, &q);
         assert_eq!(coms_mc.len(), 0);
 
         // use private function to check comments size for the question
@@ -395,15 +1051,15 @@ mod test {
     fn it_edits_a_comment_globally() {
         let q = Question::new(1, 1, 5);
         let mut asn = asn_with_students_and_questions();
-        asn.add_comment("Issac Newton", &q, 3.0, "Amateurish work".to_string());
-        asn.add_to_comment("Marie Currie", &q, 0);
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_to_comment(CURRIE, &q, 0);
         asn.edit_comment(&q, 0, 2.0, "Needs more detail".to_string());
 
-        let coms_in = asn.students_comments_for("Issac Newton", &q);
+        let coms_in = asn.students_comments_for(NEWTON, &q);
         assert_eq!(coms_in[0].deduction, 2.0);
         assert_eq!(coms_in[0].text, "Needs more detail".to_string());
 
-        let coms_mc = asn.students_comments_for("Marie Currie", &q);
+        let coms_mc = asn.students_comments_for(CURRIE, &q);
         assert_eq!(coms_mc[0].deduction, 2.0);
         assert_eq!(coms_mc[0].text, "Needs more detail".to_string());
     }
@@ -412,13 +1068,13 @@ mod test {
     fn it_gets_all_used_and_unused_comments_for_a_student_on_a_question() {
         let q = Question::new(1, 1, 5);
         let mut asn = asn_with_students_and_questions();
-        asn.add_comment("Issac Newton", &q, 3.0, "Amateurish work".to_string());
-        asn.add_comment("Albert Einstein", &q, 5.0, "Not at all correct".to_string());
-        asn.add_comment("Marie Currie", &q, 1.0, "On the right track".to_string());
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_comment(EINSTEIN, &q, 5.0, "Not at all correct".to_string());
+        asn.add_comment(CURRIE, &q, 1.0, "On the right track".to_string());
 
-        let coms_has = asn.students_comments_for("Issac Newton", &q);
+        let coms_has = asn.students_comments_for(NEWTON, &q);
         assert_eq!(coms_has.len(), 1);
-        let coms_unused = asn.unused_comments_for("Issac Newton", &q);
+        let coms_unused = asn.unused_comments_for(NEWTON, &q);
         assert_eq!(coms_unused.len(), 2);
     }
 
@@ -435,21 +1091,228 @@ mod test {
         let q2_1 = Question::new(2, 1, 10);
 
         let mut asn = asn_with_students_and_questions();
-        asn.add_comment("Issac Newton", &q1_2, 1.5, "Amateurish work".to_string());
-        asn.add_comment("Issac Newton", &q1_2, 2.0, "Try harder".to_string());
-        asn.add_comment("Issac Newton", &q2_1, 3.0, "Mind the apples".to_string());
+        asn.add_comment(NEWTON, &q1_2, 1.5, "Amateurish work".to_string());
+        asn.add_comment(NEWTON, &q1_2, 2.0, "Try harder".to_string());
+        asn.add_comment(NEWTON, &q2_1, 3.0, "Mind the apples".to_string());
 
-        assert_eq!(asn.students_mark_for("Issac Newton", &q1_1), 5.0);
-        assert_eq!(asn.students_mark_for("Issac Newton", &q1_2), 1.5);
-        assert_eq!(asn.students_mark_for("Issac Newton", &q2_1), 7.0);
-        assert_eq!(asn.students_total("Issac Newton"), 13.5);
+        assert_eq!(asn.students_mark_for(NEWTON, &q1_1), 5.0);
+        assert_eq!(asn.students_mark_for(NEWTON, &q1_2), 1.5);
+        assert_eq!(asn.students_mark_for(NEWTON, &q2_1), 7.0);
+        assert_eq!(asn.students_total(NEWTON), 13.5);
     }
 
     #[test]
     fn it_wont_give_less_than_zero_for_a_question() {
         let q1_1 = Question::new(1, 1, 5);
         let mut asn = asn_with_students_and_questions();
-        asn.add_comment("Issac Newton", &q1_1, 15.5, "Amateurish work".to_string());
-        assert_eq!(asn.students_mark_for("Issac Newton", &q1_1), 0.0);
+        asn.add_comment(NEWTON, &q1_1, 15.5, "Amateurish work".to_string());
+        assert_eq!(asn.students_mark_for(NEWTON, &q1_1), 0.0);
+    }
+
+    #[test]
+    fn it_marks_students_graded_when_they_are_given_comments() {
+        let mut asn = asn_with_students_and_questions();
+        let q = asn.get_question_at(0);
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_to_comment(CURRIE, &q, 0);
+        asn.set_graded(EINSTEIN, &asn.get_question_at(1), true);
+
+        assert!(asn.is_graded(NEWTON, &q));
+        assert!(asn.is_graded(CURRIE, &q));
+        assert!(!asn.is_graded(EINSTEIN, &q));
+        assert_eq!(asn.num_graded(&q), 2);
+        assert_eq!(asn.total_graded(), 3);
+
+        asn.set_graded(NEWTON, &q, false);
+        assert_eq!(asn.num_graded(&q), 1);
+    }
+
+    #[test]
+    fn it_only_counts_progress_for_students_being_graded() {
+        let mut asn = asn_with_students_and_questions();
+        let q = asn.get_question_at(0);
+        asn.set_graded(NEWTON, &q, true);
+        asn.withdraw_student(NEWTON);
+        assert_eq!(asn.num_graded(&q), 0);
+        asn.reinstate_student(NEWTON);
+        assert_eq!(asn.num_graded(&q), 1);
+        asn.remove_student(NEWTON);
+        assert_eq!(asn.total_graded(), 0);
+    }
+
+    #[test]
+    fn it_finds_the_next_ungraded_student_and_question() {
+        let mut asn = asn_with_students_and_questions();
+        let q1 = asn.get_question_at(0);
+        let q2 = asn.get_question_at(1);
+        asn.set_graded(NEWTON, &q2, true);
+        asn.set_graded(EINSTEIN, &q1, true);
+
+        assert_eq!(
+            asn.next_ungraded(NEWTON, &q1, false),
+            Some((NEWTON, asn.get_question_at(2)))
+        );
+        assert_eq!(
+            asn.next_ungraded(NEWTON, &q1, true),
+            Some((CURRIE, q1.clone()))
+        );
+        assert_eq!(
+            asn.next_ungraded(CURRIE, &asn.get_question_at(2), false),
+            Some((NEWTON, q1.clone()))
+        );
+
+        for s in [NEWTON, EINSTEIN, CURRIE].iter() {
+            for q in asn.get_questions().iter() {
+                asn.set_graded(*s, q, true);
+            }
+        }
+        assert_eq!(asn.next_ungraded(NEWTON, &q1, false), None);
+    }
+
+    #[test]
+    fn it_undoes_and_redoes_adding_comments() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_to_comment(CURRIE, &q, 0);
+
+        asn.undo();
+        assert_eq!(asn.students_comments_for(CURRIE, &q).len(), 0);
+        assert_eq!(asn.students_comments_for(NEWTON, &q).len(), 1);
+        asn.undo();
+        assert_eq!(asn.get_comments(&q).len(), 0);
+        assert_eq!(asn.undo(), None);
+
+        asn.redo();
+        asn.redo();
+        assert_eq!(asn.students_comments_for(CURRIE, &q)[0].id, 0);
+        assert_eq!(asn.students_comments_for(NEWTON, &q)[0].id, 0);
+        assert_eq!(asn.redo(), None);
+    }
+
+    #[test]
+    fn it_undoes_and_redoes_editing_a_comment() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.edit_comment(&q, 0, 2.0, "Needs more detail".to_string());
+
+        asn.undo();
+        let coms = asn.students_comments_for(NEWTON, &q);
+        assert_eq!(coms[0].deduction, 3.0);
+        assert_eq!(coms[0].text, "Amateurish work".to_string());
+
+        asn.redo();
+        let coms = asn.students_comments_for(NEWTON, &q);
+        assert_eq!(coms[0].deduction, 2.0);
+        assert_eq!(coms[0].text, "Needs more detail".to_string());
+    }
+
+    #[test]
+    fn it_restores_a_comment_deleted_by_removing_its_last_student() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_comment(NEWTON, &q, 1.0, "Try harder".to_string());
+        asn.add_comment(NEWTON, &q, 2.0, "Mind the apples".to_string());
+        asn.remove_from_comment(NEWTON, &q, 1);
+        assert_eq!(asn.get_comments(&q).len(), 2);
+
+        asn.undo();
+        let ids: Vec<u64> = asn.get_comments(&q).iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+        assert_eq!(asn.students_comments_for(NEWTON, &q)[1].text, "Try harder");
+
+        asn.redo();
+        assert_eq!(asn.get_comments(&q).len(), 2);
+    }
+
+    #[test]
+    fn it_undoes_removing_a_student_from_a_shared_comment() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 3.0, "Amateurish work".to_string());
+        asn.add_to_comment(CURRIE, &q, 0);
+        asn.remove_from_comment(NEWTON, &q, 0);
+
+        asn.undo();
+        assert_eq!(asn.get_comments(&q).len(), 1);
+        assert!(asn.get_comments(&q)[0].has_student(NEWTON));
+        assert!(asn.get_comments(&q)[0].has_student(CURRIE));
+    }
+
+    #[test]
+    fn it_adds_bonus_marks_up_to_what_a_question_is_out_of() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 2.0, "Amateurish work".to_string());
+        asn.add_comment(NEWTON, &q, -1.0, "Elegant proof".to_string());
+        assert_eq!(asn.students_mark_for(NEWTON, &q), 4.0);
+
+        asn.add_comment(NEWTON, &q, -3.0, "Very elegant proof".to_string());
+        assert_eq!(asn.students_mark_for(NEWTON, &q), 5.0);
+        assert_eq!(asn.students_total(NEWTON), 20.0);
+    }
+
+    #[test]
+    fn it_lets_bonus_marks_go_over_when_the_policies_allow_it() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, -2.0, "Elegant proof".to_string());
+
+        // Allowed on the question but the total is still capped.
+        asn.set_question_bonus_policy(&q, Some(BonusPolicy::AllowOver));
+        assert_eq!(asn.students_mark_for(NEWTON, &q), 7.0);
+        assert_eq!(asn.students_total(NEWTON), 20.0);
+
+        // Allowed on the assignment, and the question follows it.
+        asn.set_question_bonus_policy(&q, None);
+        asn.set_bonus_policy(BonusPolicy::AllowOver);
+        assert_eq!(asn.question_bonus_policy(&q), BonusPolicy::AllowOver);
+        assert_eq!(asn.students_total(NEWTON), 22.0);
+
+        // Capped on the question overrides the assignment.
+        asn.set_question_bonus_policy(&q, Some(BonusPolicy::Cap));
+        assert_eq!(asn.students_total(NEWTON), 20.0);
+    }
+
+    #[test]
+    fn it_scales_the_totals_of_students_being_graded() {
+        let q = Question::new(2, 1, 10);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 10.0, "Missing".to_string());
+        asn.add_comment(EINSTEIN, &q, 5.0, "Incomplete".to_string());
+        asn.withdraw_student(CURRIE);
+        asn.set_scaling(vec![Scaling::ToMean(15.0), Scaling::OutOf(100)]);
+
+        assert_eq!(asn.scaled_out_of(), 100);
+        let totals = asn.scaled_totals();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[&NEWTON], 60.0);
+        assert_eq!(totals[&EINSTEIN], 90.0);
+        assert_eq!(asn.students_scaled_total(CURRIE), 20.0);
+    }
+
+    #[test]
+    fn it_takes_late_penalties_off_the_total_after_extensions() {
+        let q = Question::new(2, 1, 10);
+        let mut asn = asn_with_students_and_questions();
+        asn.add_comment(NEWTON, &q, 4.0, "Incomplete".to_string());
+        asn.set_due_date(late::parse_date_time("2026-10-16"));
+        asn.set_late_policy(LatePolicy {
+            penalty: LatePenalty::PercentPerDay(10.0),
+            cutoff_days: Some(3),
+        });
+        let submitted = late::parse_date_time("2026-10-18 09:00");
+        asn.set_submitted(NEWTON, submitted);
+        asn.set_submitted(EINSTEIN, submitted);
+        asn.set_extension(EINSTEIN, late::parse_date_time("2026-10-18"));
+
+        assert_eq!(asn.students_days_late(NEWTON), 2);
+        assert_eq!(asn.students_late_penalty(NEWTON), 4.0);
+        assert_eq!(asn.students_marks_total(NEWTON), 16.0);
+        assert_eq!(asn.students_total(NEWTON), 12.0);
+        assert_eq!(asn.students_total(EINSTEIN), 20.0);
+        assert_eq!(asn.students_total(CURRIE), 20.0);
     }
 }
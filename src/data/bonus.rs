@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Whether bonus marks can take a mark over what it is out of.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BonusPolicy {
+    /// Bonus marks only make up for deductions. The mark is capped at what
+    /// it is out of.
+    #[default]
+    Cap,
+    /// Bonus marks can take the mark over what it is out of.
+    AllowOver,
+}
+
+impl BonusPolicy {
+    /// Limits a mark to what it is out of if the policy requires it.
+    pub fn limit(&self, mark: f32, out_of: f32) -> f32 {
+        match self {
+            BonusPolicy::Cap if mark > out_of => out_of,
+            _ => mark,
+        }
+    }
+
+    /// A short description of the policy for showing to the user.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            BonusPolicy::Cap => "Cap At Out Of",
+            BonusPolicy::AllowOver => "Allow Over Out Of",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_caps_marks_only_when_required() {
+        assert_eq!(BonusPolicy::Cap.limit(12.0, 10.0), 10.0);
+        assert_eq!(BonusPolicy::Cap.limit(8.0, 10.0), 8.0);
+        assert_eq!(BonusPolicy::AllowOver.limit(12.0, 10.0), 12.0);
+    }
+}
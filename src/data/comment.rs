@@ -1,36 +1,47 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 /// A comment for grading assignments. Rather than being applied to each
 /// student, any student that has the comment is added to the comment so that
 /// it can be used more than once.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Comment {
     /// An id that should be made unique by the owner
     pub id: u64,
-    /// The amount to deduct from the total of the question
+    /// The amount to deduct from the total of the question. A negative
+    /// deduction is a bonus that adds marks.
     pub deduction: f32,
     /// The comment text
     pub text: String,
-    // The names of the students that this comment should be applied to
-    names: HashSet<String>,
+    /// True if the text is LaTeX to put in grade sheets as it is, rather
+    /// than plain text to escape.
+    #[serde(default)]
+    pub raw_latex: bool,
+    // The ids of the students that this comment should be applied to
+    students: BTreeSet<u64>,
 }
 
 impl Comment {
-    pub fn new(id: u64, deduction: f32, text: String, student: String) -> Comment {
-        let mut names = HashSet::new();
-        names.insert(student);
+    pub fn new(id: u64, deduction: f32, text: String, student: u64) -> Comment {
+        let mut students = BTreeSet::new();
+        students.insert(student);
         Comment {
             id,
             deduction,
             text,
-            names,
+            raw_latex: false,
+            students,
         }
     }
 
+    /// Returns true if the comment adds marks rather than taking them away.
+    pub fn is_bonus(&self) -> bool {
+        self.deduction < 0.0
+    }
+
     /// Returns true if the given student has been added to the comment.
-    pub fn has_student(&self, student: &str) -> bool {
-        self.names.contains(student)
+    pub fn has_student(&self, student: u64) -> bool {
+        self.students.contains(&student)
     }
 
     /// Adds a new student to the comment.
@@ -38,19 +49,29 @@ impl Comment {
     //  Wether or not it is a problem to add or remove existing students should
     //  probably be the responsibility of the caller, even though I want this
     //  behaviour in the app, as if it happens something has gone wrong somewhere.
-    pub fn add_student(&mut self, student: String) {
-        assert!(!self.has_student(&student));
-        self.names.insert(student);
+    pub fn add_student(&mut self, student: u64) {
+        assert!(!self.has_student(student));
+        self.students.insert(student);
     }
 
     /// Removes the given student from the comment.
-    pub fn remove_student(&mut self, student: &str) {
-        self.names.remove(student);
+    pub fn remove_student(&mut self, student: u64) {
+        self.students.remove(&student);
     }
 
     /// Returns true if there are no students added to the comment.
     pub fn empty(&self) -> bool {
-        self.names.len() == 0
+        self.students.is_empty()
+    }
+}
+
+/// A deduction as it is shown to the user, with bonuses shown as marks
+/// added.
+pub fn deduction_label(deduction: f32) -> String {
+    if deduction < 0.0 {
+        format!("+{}", -deduction)
+    } else {
+        format!("-{}", deduction)
     }
 }
 
@@ -58,48 +79,46 @@ impl Comment {
 mod test {
     use super::*;
 
+    const NEWTON: u64 = 0;
+    const EINSTEIN: u64 = 1;
+
     fn make_test_comment() -> Comment {
-        Comment::new(
-            0,
-            3.0,
-            "Not an answer.".to_string(),
-            "Albert Einstein".to_string(),
-        )
+        Comment::new(0, 3.0, "Not an answer.".to_string(), EINSTEIN)
     }
 
     #[test]
     fn it_knows_which_students_it_has() {
         let comment = make_test_comment();
-        assert!(comment.has_student("Albert Einstein"));
-        assert!(!comment.has_student("Issac Newton"));
+        assert!(comment.has_student(EINSTEIN));
+        assert!(!comment.has_student(NEWTON));
     }
 
     #[test]
     fn it_adds_a_student() {
         let mut comment = make_test_comment();
-        comment.add_student("Issac Newton".to_string());
-        assert!(comment.has_student("Issac Newton"));
+        comment.add_student(NEWTON);
+        assert!(comment.has_student(NEWTON));
     }
 
     #[test]
     #[should_panic]
     fn it_panics_if_a_student_has_already_been_added() {
         let mut comment = make_test_comment();
-        comment.add_student("Albert Einstein".to_string());
+        comment.add_student(EINSTEIN);
     }
 
     #[test]
     fn it_removes_a_student() {
         let mut comment = make_test_comment();
-        comment.remove_student("Albert Einstein");
-        assert!(!comment.has_student("Albert Einstein"));
+        comment.remove_student(EINSTEIN);
+        assert!(!comment.has_student(EINSTEIN));
     }
 
     #[test]
     fn it_knows_when_it_is_empty() {
         let mut comment = make_test_comment();
         assert!(!comment.empty());
-        comment.remove_student("Albert Einstein");
+        comment.remove_student(EINSTEIN);
         assert!(comment.empty());
     }
 }
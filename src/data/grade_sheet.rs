@@ -0,0 +1,94 @@
+use crate::data::template::{self, Context, Value};
+use crate::data::{deduction_label, Assignment, Comment};
+use itertools::Itertools;
+
+/// The values a grade sheet template can use for a student. Text typed by
+/// the user is escaped for the format, and `comment_text` gives the text of
+/// each comment as it should appear in the format.
+///
+/// - `course`, `title`: the assignment's course and title.
+/// - `student`: the student's name and number. `name`, `number`, `email`,
+///   `section` are their details on their own.
+/// - `score`, `out_of`: the student's total and what it is out of, after any
+///   late penalty and scaling.
+/// - `questions`: a list of the question parts, each with `label` (like
+///   1.2), `mark`, `out_of`, and `comments`, which is empty if the student
+///   lost no marks. `has_comments` is true if there are any, and `graded` is
+///   true once the part has been graded for the student.
+/// - `comments`: a list of the comments, most marks taken off first, each
+///   with `text`, `amount` (the marks taken off or given), and one of
+///   `deduction`, `note` or `bonus` true.
+/// - `late`: true if the student lost marks for submitting late.
+///   `days_late` and `late_penalty` say how late and how many marks it cost.
+pub fn context(
+    assignment: &Assignment,
+    student: u64,
+    escape: &dyn Fn(&str) -> String,
+    comment_text: &dyn Fn(&Comment) -> String,
+) -> Context {
+    let s = assignment.get_student(student);
+    let mut ctx = Context::new();
+    template::text(&mut ctx, "course", escape(&assignment.course));
+    template::text(&mut ctx, "title", escape(&assignment.title));
+    template::text(&mut ctx, "student", escape(&s.label()));
+    template::text(&mut ctx, "name", escape(&s.name));
+    template::text(&mut ctx, "number", escape(&s.number));
+    template::text(&mut ctx, "email", escape(&s.email));
+    template::text(&mut ctx, "section", escape(&s.section));
+    template::text(&mut ctx, "score", assignment.students_scaled_total(student));
+    template::text(&mut ctx, "out_of", assignment.scaled_out_of());
+
+    let questions = assignment
+        .get_questions()
+        .iter()
+        .map(|q| {
+            let comments: Vec<Context> = assignment
+                .students_comments_for(student, q)
+                .iter()
+                .sorted_by(|a, b| b.deduction.partial_cmp(&a.deduction).unwrap())
+                .map(|c| comment_context(c, comment_text))
+                .collect();
+            let mut qctx = Context::new();
+            template::text(&mut qctx, "label", q.label());
+            template::text(&mut qctx, "mark", assignment.students_mark_for(student, q));
+            template::text(&mut qctx, "out_of", q.out_of);
+            qctx.insert(
+                "graded".to_string(),
+                Value::Bool(assignment.is_graded(student, q)),
+            );
+            qctx.insert(
+                "has_comments".to_string(),
+                Value::Bool(!comments.is_empty()),
+            );
+            qctx.insert("comments".to_string(), Value::List(comments));
+            qctx
+        })
+        .collect();
+    ctx.insert("questions".to_string(), Value::List(questions));
+
+    let penalty = assignment.students_late_penalty(student);
+    ctx.insert("late".to_string(), Value::Bool(penalty > 0.0));
+    template::text(
+        &mut ctx,
+        "days_late",
+        assignment.students_days_late(student),
+    );
+    template::text(&mut ctx, "late_penalty", deduction_label(penalty));
+    ctx
+}
+
+// The values a template can use for a comment.
+fn comment_context(comment: &Comment, comment_text: &dyn Fn(&Comment) -> String) -> Context {
+    let mut ctx = Context::new();
+    template::text(&mut ctx, "text", comment_text(comment));
+    template::text(&mut ctx, "amount", comment.deduction.abs());
+    let kinds = [
+        ("deduction", comment.deduction > 0.0),
+        ("note", comment.deduction == 0.0),
+        ("bonus", comment.is_bonus()),
+    ];
+    for (kind, is_kind) in kinds.iter() {
+        ctx.insert(kind.to_string(), Value::Bool(*is_kind));
+    }
+    ctx
+}
@@ -0,0 +1,123 @@
+use crate::data::Assignment;
+use csv::WriterBuilder;
+
+/// Converts an assignment into a CSV gradebook with one row per student.
+/// After the student's name and number there is a column for the mark on
+/// every question part, labeled `num.part`, followed by the student's total
+/// and what the assignment is out of, both after any scaling.
+pub fn convert_assignment(assignment: &Assignment) -> String {
+    let questions = assignment.get_questions();
+    let mut header = vec!["Student".to_string(), "Student Number".to_string()];
+    header.extend(questions.iter().map(|q| format!("{}.{}", q.num, q.part)));
+    header.push("Total".to_string());
+    header.push("Out Of".to_string());
+
+    let totals = assignment.scaled_totals();
+    let mut rows = vec![header];
+    rows.extend(assignment.get_students().into_iter().map(|s| {
+        let mut row = vec![s.name.clone(), s.number.clone()];
+        row.extend(
+            questions
+                .iter()
+                .map(|q| assignment.students_mark_for(s.id, q).to_string()),
+        );
+        row.push(totals[&s.id].to_string());
+        row.push(assignment.scaled_out_of().to_string());
+        row
+    }));
+
+    write_rows(rows)
+}
+
+/// Converts an assignment into a long format CSV with one row for every
+/// comment given to a student on a question.
+pub fn convert_comments(assignment: &Assignment) -> String {
+    let header = [
+        "Student",
+        "Student Number",
+        "Question",
+        "Comment Id",
+        "Deduction",
+        "Text",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect();
+
+    let mut rows = vec![header];
+    for s in assignment.get_students().iter() {
+        for q in assignment.get_questions().iter() {
+            for c in assignment.students_comments_for(s.id, q) {
+                rows.push(vec![
+                    s.name.clone(),
+                    s.number.clone(),
+                    format!("{}.{}", q.num, q.part),
+                    c.id.to_string(),
+                    c.deduction.to_string(),
+                    c.text,
+                ]);
+            }
+        }
+    }
+
+    write_rows(rows)
+}
+
+/// Writes rows of fields as CSV text, quoting fields where needed.
+/// The rows do not all need to have the same number of fields.
+pub fn write_rows(rows: Vec<Vec<String>>) -> String {
+    let mut w = WriterBuilder::new().flexible(true).from_writer(vec![]);
+    for row in rows {
+        w.write_record(&row).unwrap();
+    }
+    String::from_utf8(w.into_inner().unwrap()).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::{Question, Student};
+
+    fn make_test_asn() -> Assignment {
+        let q1 = Question::new(1, 1, 5);
+        let q2 = Question::new(1, 2, 5);
+        let q3 = Question::new(2, 1, 10);
+
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        asn.add_question(q3.num, q3.part, q3.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        let mut currie = Student::new("Currie, Marie");
+        currie.number = "001234".to_string();
+        let currie = asn.add_student(currie);
+
+        asn.add_comment(newton, &q2, 1.5, "Amateurish work".to_string());
+        asn.add_comment(newton, &q3, 3.0, "Mind the \"apples\"".to_string());
+        asn.add_comment(currie, &q1, 1.0, "On the right track".to_string());
+        asn
+    }
+
+    #[test]
+    fn it_converts_marks_to_one_row_per_student() {
+        let csv = convert_assignment(&make_test_asn());
+        assert_eq!(
+            csv,
+            "Student,Student Number,1.1,1.2,2.1,Total,Out Of\n\
+             Issac Newton,,5,3.5,7,15.5,20\n\
+             \"Currie, Marie\",001234,4,5,10,19,20\n"
+        );
+    }
+
+    #[test]
+    fn it_converts_comments_to_one_row_per_comment() {
+        let csv = convert_comments(&make_test_asn());
+        assert_eq!(
+            csv,
+            "Student,Student Number,Question,Comment Id,Deduction,Text\n\
+             Issac Newton,,1.2,0,1.5,Amateurish work\n\
+             Issac Newton,,2.1,1,3,\"Mind the \"\"apples\"\"\"\n\
+             \"Currie, Marie\",001234,1.1,2,1,On the right track\n"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::data::{Assignment, Comment, Question};
+use serde::{Deserialize, Serialize};
+
+/// The most actions that will be kept for undoing.
+pub const MAX_HISTORY: usize = 200;
+
+/// A grading action that changed an assignment, with everything needed to
+/// undo and redo it.
+//  Internally tagged so that each action is saved as a single flat record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action")]
+pub enum Action {
+    /// A new comment was created for a question.
+    CommentCreated {
+        question: Question,
+        comment: Comment,
+    },
+    /// A student was given an existing comment.
+    StudentAdded {
+        question: Question,
+        id: u64,
+        student: u64,
+    },
+    /// A comment's deduction and text were changed for every student.
+    CommentEdited {
+        question: Question,
+        id: u64,
+        old: (f32, String),
+        new: (f32, String),
+    },
+    /// A student was taken off a comment. Keeps the whole comment as it was
+    /// beforehand, because taking off the last student deletes the comment.
+    StudentRemoved {
+        question: Question,
+        student: u64,
+        comment: Comment,
+    },
+}
+
+impl Action {
+    /// A short description of the action for showing to the user. Students
+    /// are named using the assignment the action was taken on.
+    pub fn describe(&self, assignment: &Assignment) -> String {
+        match self {
+            Action::CommentCreated { question, comment } => {
+                format!(
+                    "add comment to {}.{}: {}",
+                    question.num, question.part, comment.text
+                )
+            }
+            Action::StudentAdded {
+                question, student, ..
+            } => format!(
+                "add comment to {} on {}.{}",
+                assignment.get_student(*student).label(),
+                question.num,
+                question.part
+            ),
+            Action::CommentEdited { question, new, .. } => {
+                format!(
+                    "edit comment on {}.{}: {}",
+                    question.num, question.part, new.1
+                )
+            }
+            Action::StudentRemoved {
+                question,
+                student,
+                comment,
+            } => format!(
+                "remove comment from {} on {}.{}: {}",
+                assignment.get_student(*student).label(),
+                question.num,
+                question.part,
+                comment.text
+            ),
+        }
+    }
+}
+
+/// The undo and redo stacks of grading actions for an assignment.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    undo: Vec<Action>,
+    redo: Vec<Action>,
+}
+
+impl History {
+    /// Records a newly performed action. Anything that could be redone is
+    /// dropped, since it no longer follows from the current state.
+    pub fn record(&mut self, action: Action) {
+        self.redo.clear();
+        self.push_undo(action);
+    }
+
+    /// Takes the most recent action off the undo stack and moves it to the
+    /// redo stack. The caller is responsible for reversing it.
+    pub fn undo(&mut self) -> Option<Action> {
+        let action = self.undo.pop()?;
+        self.redo.push(action.clone());
+        Some(action)
+    }
+
+    /// Takes the most recently undone action off the redo stack and moves it
+    /// back to the undo stack. The caller is responsible for performing it.
+    pub fn redo(&mut self) -> Option<Action> {
+        let action = self.redo.pop()?;
+        self.push_undo(action.clone());
+        Some(action)
+    }
+
+    /// Forgets all actions.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    // Pushes onto the undo stack, dropping the oldest action if it is full.
+    fn push_undo(&mut self, action: Action) {
+        if self.undo.len() >= MAX_HISTORY {
+            self.undo.remove(0);
+        }
+        self.undo.push(action);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::Student;
+
+    fn make_action(id: u64) -> Action {
+        Action::StudentAdded {
+            question: Question::new(1, 1, 5),
+            id,
+            student: 0,
+        }
+    }
+
+    #[test]
+    fn it_undoes_and_redoes_in_order() {
+        let mut history = History::default();
+        history.record(make_action(0));
+        history.record(make_action(1));
+
+        assert_eq!(history.undo(), Some(make_action(1)));
+        assert_eq!(history.undo(), Some(make_action(0)));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(make_action(0)));
+        assert_eq!(history.redo(), Some(make_action(1)));
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn it_forgets_redo_when_a_new_action_is_recorded() {
+        let mut history = History::default();
+        history.record(make_action(0));
+        history.undo();
+        history.record(make_action(1));
+
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(make_action(1)));
+    }
+
+    #[test]
+    fn it_keeps_a_limited_number_of_actions() {
+        let mut history = History::default();
+        for id in 0..(MAX_HISTORY as u64 + 10) {
+            history.record(make_action(id));
+        }
+        let mut count = 0;
+        while history.undo().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, MAX_HISTORY);
+    }
+
+    #[test]
+    fn it_describes_actions_with_the_students_name() {
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        let action = Action::StudentAdded {
+            question: Question::new(1, 2, 5),
+            id: 0,
+            student: newton,
+        };
+        assert_eq!(action.describe(&asn), "add comment to Issac Newton on 1.2");
+    }
+}
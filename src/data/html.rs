@@ -0,0 +1,127 @@
+use crate::data::template::Context;
+use crate::data::{grade_sheet, Assignment};
+
+/// The HTML grade sheet layout, the same as the default LaTeX one. The page
+/// is self-contained, with its styles inline, so it can be emailed or
+/// uploaded on its own. See `grade_sheet::context` for the names it uses.
+pub const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{{course}} {{title}} Grading</title>
+<style>
+body { font-family: sans-serif; max-width: 40em; margin: 2em auto; }
+h1 { text-align: center; }
+.student { text-align: center; }
+.deduction { color: #c00; font-weight: bold; }
+.note { font-weight: bold; }
+.bonus { color: #060; font-weight: bold; }
+</style>
+</head>
+<body>
+<h1>{{course}} {{title}} Grading</h1>
+<p class="student">{{student}}<br><strong>Score: {{score}}/{{out_of}}</strong></p>
+{{#questions}}
+<h2>{{label}} &ndash; {{mark}}/{{out_of}}</h2>
+{{^graded}}
+<p>Not Graded</p>
+{{/graded}}
+{{#graded}}
+{{^comments}}
+<p>Well Done</p>
+{{/comments}}
+{{/graded}}
+{{#has_comments}}
+<ul>
+{{#comments}}
+{{#deduction}}
+<li><span class="deduction">-{{amount}}</span> {{text}}</li>
+{{/deduction}}
+{{#note}}
+<li><span class="note">Note</span> {{text}}</li>
+{{/note}}
+{{#bonus}}
+<li><span class="bonus">+{{amount}}</span> {{text}}</li>
+{{/bonus}}
+{{/comments}}
+</ul>
+{{/has_comments}}
+{{/questions}}
+{{#late}}
+<h2>Late Penalty &ndash; {{late_penalty}}</h2>
+<p>Submitted {{days_late}} days late</p>
+{{/late}}
+</body>
+</html>
+"#;
+
+/// The values the HTML grade sheet template is filled in with, with all
+/// text escaped.
+pub fn context(assignment: &Assignment, student: u64) -> Context {
+    grade_sheet::context(assignment, student, &escape, &|c| escape(&c.text))
+}
+
+/// Escapes the characters HTML treats specially, so the text comes out as
+/// it was typed.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::template::Template;
+    use crate::data::{Question, Student};
+
+    fn convert(assignment: &Assignment, student: u64) -> String {
+        let template = Template::parse(DEFAULT_TEMPLATE).unwrap();
+        template.render(&context(assignment, student)).unwrap()
+    }
+
+    #[test]
+    fn it_lays_out_an_html_grade_sheet() {
+        let q1 = Question::new(1, 1, 10);
+        let q2 = Question::new(1, 2, 5);
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        asn.add_comment(newton, &q1, -1.0, "Elegant".to_string());
+        asn.add_comment(newton, &q1, 2.5, "Check x < y".to_string());
+        asn.set_graded(newton, &q2, true);
+
+        let html = convert(&asn, newton);
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.contains("<title>CS 1000 Assignment 5 Grading</title>\n"));
+        assert!(html.contains("Issac Newton<br><strong>Score: 13.5/15</strong>"));
+        assert!(html.contains(
+            "<h2>1.1 &ndash; 8.5/10</h2>
+<ul>
+<li><span class=\"deduction\">-2.5</span> Check x &lt; y</li>
+<li><span class=\"bonus\">+1</span> Elegant</li>
+</ul>
+<h2>1.2 &ndash; 5/5</h2>
+<p>Well Done</p>
+</body>"
+        ));
+    }
+
+    #[test]
+    fn it_escapes_html_special_characters() {
+        assert_eq!(
+            escape("<b>\"Tom\" & 'Jerry'</b>"),
+            "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// The layout dates and times are entered and shown in.
+pub const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// How marks are taken off for each day a submission is late.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LatePenalty {
+    /// Late submissions lose no marks.
+    #[default]
+    None,
+    /// A percent of what the assignment is out of for each day, or part of a
+    /// day, late.
+    PercentPerDay(f32),
+    /// A fixed number of marks for being late at all.
+    Fixed(f32),
+}
+
+/// The penalty for late submissions, and when they get zero instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatePolicy {
+    pub penalty: LatePenalty,
+    /// Submissions more than this many days late get zero. A cutoff of 0
+    /// gives zero to any late submission. None means there is no cutoff.
+    pub cutoff_days: Option<u32>,
+}
+
+impl LatePolicy {
+    /// The marks taken off a total for being the given number of days late.
+    /// It is never more than the total, so a total never goes below zero.
+    pub fn penalty(&self, days_late: u32, total: f32, out_of: u32) -> f32 {
+        if days_late == 0 {
+            return 0.0;
+        }
+        if self.cutoff_days.is_some_and(|c| days_late > c) {
+            return total.max(0.0);
+        }
+        let penalty = match self.penalty {
+            LatePenalty::None => 0.0,
+            LatePenalty::PercentPerDay(percent) => {
+                out_of as f32 * percent / 100.0 * days_late as f32
+            }
+            LatePenalty::Fixed(marks) => marks,
+        };
+        penalty.min(total).max(0.0)
+    }
+
+    /// A short description of the policy for showing to the user.
+    pub fn describe(&self) -> String {
+        let penalty = match self.penalty {
+            LatePenalty::None => "No Penalty".to_string(),
+            LatePenalty::PercentPerDay(percent) => format!("{}% Per Day", percent),
+            LatePenalty::Fixed(marks) => format!("{} Marks", marks),
+        };
+        match self.cutoff_days {
+            Some(0) => "Zero If Late".to_string(),
+            Some(days) => format!("{}, Zero After {} Days", penalty, days),
+            None => penalty,
+        }
+    }
+}
+
+/// The number of days, counting any part of a day as a whole day, that a
+/// submission was after its due date. Zero if it was on time.
+pub fn days_late(submitted: NaiveDateTime, due: NaiveDateTime) -> u32 {
+    let seconds = (submitted - due).num_seconds();
+    if seconds <= 0 {
+        0
+    } else {
+        ((seconds + 86399) / 86400) as u32
+    }
+}
+
+/// Reads a date and time in the `DATE_TIME_FORMAT` layout. A date on its own
+/// is taken to be the last minute of that day.
+/// Returns None if the text is not a date.
+pub fn parse_date_time(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    NaiveDateTime::parse_from_str(text, DATE_TIME_FORMAT)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(23, 59, 0)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        parse_date_time(text).unwrap()
+    }
+
+    #[test]
+    fn it_counts_part_of_a_day_as_a_whole_day_late() {
+        let due = at("2026-10-16 23:59");
+        assert_eq!(days_late(at("2026-10-16 12:00"), due), 0);
+        assert_eq!(days_late(due, due), 0);
+        assert_eq!(days_late(at("2026-10-17 00:00"), due), 1);
+        assert_eq!(days_late(at("2026-10-17 23:59"), due), 1);
+        assert_eq!(days_late(at("2026-10-18 00:01"), due), 2);
+    }
+
+    #[test]
+    fn it_reads_dates_with_or_without_a_time() {
+        assert_eq!(at("2026-10-16"), at("2026-10-16 23:59"));
+        assert!(parse_date_time("16/10/2026").is_none());
+    }
+
+    #[test]
+    fn it_takes_a_percent_of_the_out_of_per_day() {
+        let policy = LatePolicy {
+            penalty: LatePenalty::PercentPerDay(10.0),
+            cutoff_days: Some(3),
+        };
+        assert_eq!(policy.penalty(0, 30.0, 40), 0.0);
+        assert_eq!(policy.penalty(2, 30.0, 40), 8.0);
+        assert_eq!(policy.penalty(3, 10.0, 40), 10.0);
+        assert_eq!(policy.penalty(4, 30.0, 40), 30.0);
+    }
+
+    #[test]
+    fn it_takes_fixed_marks_or_everything_after_the_cutoff() {
+        let fixed = LatePolicy {
+            penalty: LatePenalty::Fixed(5.0),
+            cutoff_days: None,
+        };
+        assert_eq!(fixed.penalty(7, 30.0, 40), 5.0);
+        let zero = LatePolicy {
+            penalty: LatePenalty::None,
+            cutoff_days: Some(0),
+        };
+        assert_eq!(zero.penalty(1, 30.0, 40), 30.0);
+        assert_eq!(zero.describe(), "Zero If Late");
+    }
+}
@@ -1,62 +1,245 @@
-use crate::data::{Assignment, Comment};
-use itertools::Itertools;
-
-pub fn convert_assignment(assignment: &Assignment, student: &String) -> String {
-    vec![
-        "\\documentclass{article}".to_string(),
-        "\\usepackage{fullpage}".to_string(),
-        "\\usepackage{xcolor}".to_string(),
-        format!(
-            "\\title{{{} {} Grading}}",
-            assignment.course, assignment.title
-        ),
-        format!(
-            "\\author{{{} \\\\ \\textbf{{Score: {}/{}}} }}",
-            student,
-            assignment.students_total(student),
-            assignment.out_of()
-        ),
-        "\\date{\\today}".to_string(),
-        "\\begin{document}".to_string(),
-        "\\maketitle".to_string(),
-        convert_questions(assignment, student),
-        "\\end{document}".to_string(),
-    ]
-    .join("\n")
+use crate::data::template::Context;
+use crate::data::{grade_sheet, Assignment, Comment};
+use serde::{Deserialize, Serialize};
+
+/// A locally installed LaTeX engine that grade sheets can be compiled to PDF
+/// with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LatexEngine {
+    Pdflatex,
+    Xelatex,
+    Tectonic,
 }
 
-fn convert_questions(assignment: &Assignment, student: &String) -> String {
-    assignment
-        .get_questions()
-        .iter()
-        .fold(String::new(), |acc, q| {
-            acc + "\n"
-                + &format!(
-                    "\\section*{{{}.{} -- {}/{}}}\n",
-                    q.num,
-                    q.part,
-                    assignment.students_mark_for(student, &q),
-                    q.out_of
-                )
-                + &match assignment
-                    .students_comments_for(student, &q)
-                    .iter()
-                    .sorted_by(|a, b| b.deduction.partial_cmp(&a.deduction).unwrap())
-                    .fold(String::new(), |acc, c| acc + "\n" + &convert_comment(&c))
-                {
-                    s if s.is_empty() => "Well Done".to_string(),
-                    s => "\\begin{description}".to_string() + &s + "\n" + "\\end{description}",
-                }
-        })
+impl LatexEngine {
+    /// All of the supported engines.
+    pub fn all() -> Vec<LatexEngine> {
+        vec![
+            LatexEngine::Pdflatex,
+            LatexEngine::Xelatex,
+            LatexEngine::Tectonic,
+        ]
+    }
+
+    /// The name of the engine's command.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LatexEngine::Pdflatex => "pdflatex",
+            LatexEngine::Xelatex => "xelatex",
+            LatexEngine::Tectonic => "tectonic",
+        }
+    }
+
+    /// Finds an engine by its name, ignoring case.
+    pub fn from_name(name: &str) -> Option<LatexEngine> {
+        let name = name.to_lowercase();
+        LatexEngine::all().into_iter().find(|e| e.name() == name)
+    }
 }
 
-pub fn convert_comment(comment: &Comment) -> String {
-    if comment.deduction > 0.0 {
-        format!(
-            "\\item[\\color{{red}}-{}] {}",
-            comment.deduction, comment.text
+/// The grade sheet layout used when an assignment has no template of its
+/// own. See `grade_sheet::context` for the names it can use.
+pub const DEFAULT_TEMPLATE: &str = r"\documentclass{article}
+\usepackage{fullpage}
+\usepackage{xcolor}
+\title{{{course}} {{title}} Grading}
+\author{{{student}} \\ \textbf{Score: {{score}}/{{out_of}}} }
+\date{\today}
+\begin{document}
+\maketitle
+
+{{#questions}}
+\section*{{{label}} -- {{mark}}/{{out_of}}}
+{{^graded}}
+Not Graded
+{{/graded}}
+{{#graded}}
+{{^comments}}
+Well Done
+{{/comments}}
+{{/graded}}
+{{#has_comments}}
+\begin{description}
+{{#comments}}
+{{#deduction}}
+\item[\color{red}-{{amount}}] {{text}}
+{{/deduction}}
+{{#note}}
+\item[Note] {{text}}
+{{/note}}
+{{#bonus}}
+\item[\color{green!50!black}+{{amount}}] {{text}}
+{{/bonus}}
+{{/comments}}
+\end{description}
+{{/has_comments}}
+{{/questions}}
+{{#late}}
+
+\section*{Late Penalty -- {{late_penalty}}}
+Submitted {{days_late}} days late
+{{/late}}
+\end{document}";
+
+/// The values a LaTeX grade sheet template is filled in with. Comments
+/// marked as raw LaTeX are put in as they were typed, and all other text is
+/// escaped.
+pub fn context(assignment: &Assignment, student: u64) -> Context {
+    grade_sheet::context(assignment, student, &escape, &|c: &Comment| {
+        if c.raw_latex {
+            c.text.clone()
+        } else {
+            escape(&c.text)
+        }
+    })
+}
+
+/// Escapes the characters LaTeX treats specially, so the text comes out as
+/// it was typed.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::late::{self, LatePenalty, LatePolicy};
+    use crate::data::template::Template;
+    use crate::data::{Question, Student};
+
+    fn convert_default(assignment: &Assignment, student: u64) -> String {
+        let template = Template::parse(DEFAULT_TEMPLATE).unwrap();
+        template.render(&context(assignment, student)).unwrap()
+    }
+
+    fn make_test_asn() -> (Assignment, u64) {
+        let q1 = Question::new(1, 1, 10);
+        let q2 = Question::new(1, 2, 5);
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        asn.add_comment(newton, &q1, -1.0, "Elegant".to_string());
+        asn.add_comment(newton, &q1, 0.0, "See me".to_string());
+        asn.add_comment(newton, &q1, 2.5, "Amateurish work".to_string());
+        asn.set_graded(newton, &q2, true);
+        (asn, newton)
+    }
+
+    #[test]
+    fn it_lays_out_the_default_grade_sheet() {
+        let (asn, newton) = make_test_asn();
+        assert_eq!(
+            convert_default(&asn, newton),
+            "\\documentclass{article}
+\\usepackage{fullpage}
+\\usepackage{xcolor}
+\\title{CS 1000 Assignment 5 Grading}
+\\author{Issac Newton \\\\ \\textbf{Score: 13.5/15} }
+\\date{\\today}
+\\begin{document}
+\\maketitle
+
+\\section*{1.1 -- 8.5/10}
+\\begin{description}
+\\item[\\color{red}-2.5] Amateurish work
+\\item[Note] See me
+\\item[\\color{green!50!black}+1] Elegant
+\\end{description}
+\\section*{1.2 -- 5/5}
+Well Done
+\\end{document}"
+        );
+    }
+
+    #[test]
+    fn it_says_when_a_part_has_not_been_graded() {
+        let (mut asn, newton) = make_test_asn();
+        asn.set_graded(newton, &Question::new(1, 2, 5), false);
+        let latex = convert_default(&asn, newton);
+        assert!(latex.contains("\\section*{1.2 -- 5/5}\nNot Graded\n"));
+        assert!(!latex.contains("Well Done"));
+    }
+
+    #[test]
+    fn it_fills_in_a_custom_template() {
+        let (asn, newton) = make_test_asn();
+        let template = Template::parse(
+            "{{name}}: {{score}}\n{{#questions}}{{label}}{{#comments}} {{amount}}{{/comments}};{{/questions}}",
         )
-    } else {
-        format!("\\item[Note] {}", comment.text)
+        .unwrap();
+        assert_eq!(
+            template.render(&context(&asn, newton)).unwrap(),
+            "Issac Newton: 13.5\n1.1 2.5 0 1;1.2;"
+        );
+        let unknown = Template::parse("{{signature}}").unwrap();
+        assert!(unknown.render(&context(&asn, newton)).is_err());
+    }
+
+    #[test]
+    fn it_adds_a_late_penalty_section_only_when_late() {
+        let q = Question::new(1, 1, 10);
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q.num, q.part, q.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        asn.set_graded(newton, &q, true);
+        asn.set_due_date(late::parse_date_time("2026-10-16"));
+        asn.set_late_policy(LatePolicy {
+            penalty: LatePenalty::Fixed(2.0),
+            cutoff_days: None,
+        });
+        assert!(!convert_default(&asn, newton).contains("Late Penalty"));
+
+        asn.set_submitted(newton, late::parse_date_time("2026-10-17 10:00"));
+        let latex = convert_default(&asn, newton);
+        assert!(latex.contains("Score: 8/10"));
+        assert!(latex.ends_with(
+            "Well Done\n\n\\section*{Late Penalty -- -2}\nSubmitted 1 days late\n\\end{document}"
+        ));
+
+        asn.set_late_policy(LatePolicy::default());
+        assert!(!convert_default(&asn, newton).contains("Late Penalty"));
+    }
+
+    #[test]
+    fn it_escapes_latex_special_characters() {
+        assert_eq!(
+            escape("use 100% of the _buffer_ & check $x"),
+            "use 100\\% of the \\_buffer\\_ \\& check \\$x"
+        );
+        assert_eq!(
+            escape("#{a}^~\\"),
+            "\\#\\{a\\}\\textasciicircum{}\\textasciitilde{}\\textbackslash{}"
+        );
+    }
+
+    #[test]
+    fn it_escapes_user_text_but_not_raw_latex_comments() {
+        let q = Question::new(1, 1, 10);
+        let mut asn = Assignment::new("Lab_1".to_string(), "CS 1000 & 1001".to_string());
+        asn.add_question(q.num, q.part, q.out_of);
+        let student = asn.add_student(Student::new("Ada_Lovelace"));
+        asn.add_comment(student, &q, 1.0, "Rename my_var".to_string());
+        asn.add_comment(student, &q, 2.0, "Use $x^2$".to_string());
+        asn.set_comment_raw_latex(&q, 1, true);
+
+        let latex = convert_default(&asn, student);
+        assert!(latex.contains("\\title{CS 1000 \\& 1001 Lab\\_1 Grading}"));
+        assert!(latex.contains("\\author{Ada\\_Lovelace \\\\"));
+        assert!(latex.contains("] Rename my\\_var\n"));
+        assert!(latex.contains("] Use $x^2$\n"));
     }
 }
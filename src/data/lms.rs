@@ -0,0 +1,245 @@
+use crate::data::{gradebook, Assignment};
+use itertools::Itertools;
+
+/// Learning management systems that accept grade imports as CSV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lms {
+    Moodle,
+    Canvas,
+    Blackboard,
+}
+
+impl Lms {
+    /// All of the supported systems.
+    pub fn all() -> Vec<Lms> {
+        vec![Lms::Moodle, Lms::Canvas, Lms::Blackboard]
+    }
+
+    /// The name of the system, as used in file names and on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lms::Moodle => "moodle",
+            Lms::Canvas => "canvas",
+            Lms::Blackboard => "blackboard",
+        }
+    }
+
+    /// Finds a system by its name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Lms> {
+        let name = name.to_lowercase();
+        Lms::all().into_iter().find(|l| l.name() == name)
+    }
+}
+
+/// Converts an assignment into the CSV layout the given system imports.
+/// The grades are the students' totals after any scaling.
+///
+/// - Moodle: an identifier column to map to a user field, the grade column
+///   named after the assignment, and a feedback column.
+/// - Canvas: the gradebook export layout, with the points possible row.
+///   Students are matched on their number as the SIS user id and their email
+///   as the SIS login id. Canvas does not import comments, so there is no
+///   feedback column.
+/// - Blackboard: the Grade Center layout with the total points in the grade
+///   column header, followed by the feedback to learner columns.
+pub fn convert_assignment(assignment: &Assignment, lms: Lms) -> String {
+    let title = assignment.title.clone();
+    let out_of = assignment.scaled_out_of();
+    let totals = assignment.scaled_totals();
+    let mut rows: Vec<Vec<String>> = Vec::new();
+
+    match lms {
+        Lms::Moodle => {
+            rows.push(strings(&["Identifier", "Full name", &title, "Feedback"]));
+            for s in assignment.get_students().iter() {
+                rows.push(vec![
+                    s.identifier().to_string(),
+                    s.name.clone(),
+                    totals[&s.id].to_string(),
+                    feedback(assignment, s.id),
+                ]);
+            }
+        }
+        Lms::Canvas => {
+            let grade_col = format!("{} ({})", title, out_of);
+            rows.push(strings(&[
+                "Student",
+                "ID",
+                "SIS User ID",
+                "SIS Login ID",
+                "Section",
+                &grade_col,
+            ]));
+            rows.push(strings(&[
+                "    Points Possible",
+                "",
+                "",
+                "",
+                "",
+                &out_of.to_string(),
+            ]));
+            for s in assignment.get_students().iter() {
+                let (first, last) = split_name(&s.name);
+                rows.push(vec![
+                    format!("{}, {}", last, first),
+                    String::new(),
+                    s.number.clone(),
+                    s.email.clone(),
+                    s.section.clone(),
+                    totals[&s.id].to_string(),
+                ]);
+            }
+        }
+        Lms::Blackboard => {
+            let grade_col = format!("{} [Total Pts: {} Score]", title, out_of);
+            rows.push(strings(&[
+                "Last Name",
+                "First Name",
+                "Username",
+                &grade_col,
+                "Feedback to Learner",
+                "Feedback Format",
+            ]));
+            for s in assignment.get_students().iter() {
+                let (first, last) = split_name(&s.name);
+                rows.push(vec![
+                    last,
+                    first,
+                    s.identifier().to_string(),
+                    totals[&s.id].to_string(),
+                    feedback(assignment, s.id),
+                    "PLAIN_TEXT".to_string(),
+                ]);
+            }
+        }
+    }
+
+    gradebook::write_rows(rows)
+}
+
+/// Assembles a student's comments into plain feedback text, with a line for
+/// each question part that they lost marks on.
+pub fn feedback(assignment: &Assignment, student: u64) -> String {
+    let lines: Vec<String> = assignment
+        .get_questions()
+        .iter()
+        .filter_map(|q| {
+            let comments = assignment.students_comments_for(student, q);
+            if comments.is_empty() {
+                return None;
+            }
+            Some(format!(
+                "{}.{} ({}/{}): {}",
+                q.num,
+                q.part,
+                assignment.students_mark_for(student, q),
+                q.out_of,
+                comments
+                    .iter()
+                    .sorted_by(|a, b| b.deduction.partial_cmp(&a.deduction).unwrap())
+                    .map(|c| c.text.clone())
+                    .join("; ")
+            ))
+        })
+        .collect();
+
+    if lines.is_empty() {
+        "Well Done".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+// Splits a name into first and last names on the last space.
+fn split_name(student: &str) -> (String, String) {
+    match student.trim().rsplit_once(' ') {
+        Some((first, last)) => (first.to_string(), last.to_string()),
+        None => (String::new(), student.trim().to_string()),
+    }
+}
+
+fn strings(fields: &[&str]) -> Vec<String> {
+    fields.iter().map(|f| f.to_string()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::{Question, Student};
+
+    const NEWTON: u64 = 0;
+    const CURRIE: u64 = 1;
+
+    fn make_test_asn() -> Assignment {
+        let q1 = Question::new(1, 1, 5);
+        let q2 = Question::new(1, 2, 5);
+
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        asn.add_student(Student::new("Issac Newton"));
+        let mut currie = Student::new("Marie Currie");
+        currie.number = "001234".to_string();
+        currie.email = "mcurrie@uni.ca".to_string();
+        currie.section = "A01".to_string();
+        asn.add_student(currie);
+
+        asn.add_comment(NEWTON, &q1, 1.0, "Try harder".to_string());
+        asn.add_comment(NEWTON, &q1, 2.0, "Amateurish work".to_string());
+        asn.add_comment(NEWTON, &q2, 0.5, "Mind the apples".to_string());
+        asn
+    }
+
+    #[test]
+    fn it_finds_systems_by_name() {
+        assert_eq!(Lms::from_name("Moodle"), Some(Lms::Moodle));
+        assert_eq!(Lms::from_name("canvas"), Some(Lms::Canvas));
+        assert_eq!(Lms::from_name("BLACKBOARD"), Some(Lms::Blackboard));
+        assert_eq!(Lms::from_name("d2l"), None);
+    }
+
+    #[test]
+    fn it_assembles_feedback_from_comments() {
+        let asn = make_test_asn();
+        assert_eq!(
+            feedback(&asn, NEWTON),
+            "1.1 (2/5): Amateurish work; Try harder\n1.2 (4.5/5): Mind the apples"
+        );
+        assert_eq!(feedback(&asn, CURRIE), "Well Done");
+    }
+
+    #[test]
+    fn it_converts_to_the_moodle_layout() {
+        let csv = convert_assignment(&make_test_asn(), Lms::Moodle);
+        assert_eq!(
+            csv,
+            "Identifier,Full name,Assignment 5,Feedback\n\
+             Issac Newton,Issac Newton,6.5,\"1.1 (2/5): Amateurish work; Try harder\n1.2 (4.5/5): Mind the apples\"\n\
+             001234,Marie Currie,10,Well Done\n"
+        );
+    }
+
+    #[test]
+    fn it_converts_to_the_canvas_layout() {
+        let csv = convert_assignment(&make_test_asn(), Lms::Canvas);
+        assert_eq!(
+            csv,
+            "Student,ID,SIS User ID,SIS Login ID,Section,Assignment 5 (10)\n    \
+             Points Possible,,,,,10\n\
+             \"Newton, Issac\",,,,,6.5\n\
+             \"Currie, Marie\",,001234,mcurrie@uni.ca,A01,10\n"
+        );
+    }
+
+    #[test]
+    fn it_converts_to_the_blackboard_layout() {
+        let csv = convert_assignment(&make_test_asn(), Lms::Blackboard);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "Last Name,First Name,Username,Assignment 5 [Total Pts: 10 Score],\
+             Feedback to Learner,Feedback Format"
+        );
+        assert!(csv.ends_with("Currie,Marie,001234,10,Well Done,PLAIN_TEXT\n"));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::data::template::Context;
+use crate::data::{grade_sheet, Assignment};
+
+/// The Markdown grade sheet layout, the same as the default LaTeX one. See
+/// `grade_sheet::context` for the names it uses.
+pub const DEFAULT_TEMPLATE: &str = "# {{course}} {{title}} Grading
+
+{{student}}
+
+**Score: {{score}}/{{out_of}}**
+{{#questions}}
+
+## {{label}} -- {{mark}}/{{out_of}}
+
+{{^graded}}
+Not Graded
+{{/graded}}
+{{#graded}}
+{{^comments}}
+Well Done
+{{/comments}}
+{{/graded}}
+{{#comments}}
+{{#deduction}}
+- **-{{amount}}** {{text}}
+{{/deduction}}
+{{#note}}
+- **Note** {{text}}
+{{/note}}
+{{#bonus}}
+- **+{{amount}}** {{text}}
+{{/bonus}}
+{{/comments}}
+{{/questions}}
+{{#late}}
+
+## Late Penalty -- {{late_penalty}}
+
+Submitted {{days_late}} days late
+{{/late}}
+";
+
+/// The values the Markdown grade sheet template is filled in with, with all
+/// text escaped.
+pub fn context(assignment: &Assignment, student: u64) -> Context {
+    grade_sheet::context(assignment, student, &escape, &|c| escape(&c.text))
+}
+
+/// Escapes the characters Markdown treats specially, so the text comes out
+/// as it was typed.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>#|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::template::Template;
+    use crate::data::{Question, Student};
+
+    fn convert(assignment: &Assignment, student: u64) -> String {
+        let template = Template::parse(DEFAULT_TEMPLATE).unwrap();
+        template.render(&context(assignment, student)).unwrap()
+    }
+
+    #[test]
+    fn it_lays_out_a_markdown_grade_sheet() {
+        let q1 = Question::new(1, 1, 10);
+        let q2 = Question::new(1, 2, 5);
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        asn.add_comment(newton, &q1, -1.0, "Elegant".to_string());
+        asn.add_comment(newton, &q1, 0.0, "See me".to_string());
+        asn.add_comment(newton, &q1, 2.5, "Use *args".to_string());
+        asn.set_graded(newton, &q2, true);
+        assert_eq!(
+            convert(&asn, newton),
+            "# CS 1000 Assignment 5 Grading
+
+Issac Newton
+
+**Score: 13.5/15**
+
+## 1.1 -- 8.5/10
+
+- **-2.5** Use \\*args
+- **Note** See me
+- **+1** Elegant
+
+## 1.2 -- 5/5
+
+Well Done
+"
+        );
+    }
+
+    #[test]
+    fn it_escapes_markdown_special_characters() {
+        assert_eq!(
+            escape("a_b *c* [d](e) <f> #g | `h` \\"),
+            "a\\_b \\*c\\* \\[d\\](e) \\<f\\> \\#g \\| \\`h\\` \\\\"
+        );
+    }
+}
@@ -1,7 +1,26 @@
 pub use self::assignment::Assignment;
-pub use self::comment::Comment;
+pub use self::bonus::BonusPolicy;
+pub use self::comment::{deduction_label, Comment};
+pub use self::history::{Action, History};
 pub use self::question::Question;
+pub use self::scaling::Scaling;
+pub use self::student::Student;
 mod assignment;
+mod bonus;
 mod comment;
+pub mod grade_sheet;
+pub mod gradebook;
+mod history;
+pub mod html;
+pub mod late;
 pub mod latex;
+pub mod lms;
+pub mod markdown;
 mod question;
+pub mod roster;
+pub mod scaling;
+pub mod search;
+pub mod stats;
+mod student;
+pub mod template;
+pub mod usage;
@@ -15,4 +15,15 @@ impl Question {
     pub fn new(num: u32, part: u32, out_of: u32) -> Self {
         Self { num, part, out_of }
     }
+
+    /// The question number and part, like 1.2.
+    pub fn label(&self) -> String {
+        format!("{}.{}", self.num, self.part)
+    }
+
+    /// True if both are the same question part, regardless of what they are
+    /// out of.
+    pub fn same_part(&self, other: &Question) -> bool {
+        self.num == other.num && self.part == other.part
+    }
 }
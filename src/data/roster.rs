@@ -0,0 +1,236 @@
+use crate::data::{Assignment, Student};
+use csv::{ReaderBuilder, StringRecord, Trim};
+
+/// The headers of the roster columns that hold each student detail. Headers
+/// are matched ignoring case and surrounding spaces. Only the name column
+/// must be in the roster; an empty header means the detail is not imported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RosterColumns {
+    pub name: String,
+    pub number: String,
+    pub email: String,
+    pub section: String,
+}
+
+impl Default for RosterColumns {
+    fn default() -> RosterColumns {
+        RosterColumns {
+            name: "Name".to_string(),
+            number: "Student Number".to_string(),
+            email: "Email".to_string(),
+            section: "Section".to_string(),
+        }
+    }
+}
+
+/// What happened to the rows of an imported roster.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportReport {
+    /// The names of the students that were added.
+    pub added: Vec<String>,
+    /// Rows for students that were already in the assignment, or earlier in
+    /// the roster, and were skipped.
+    pub duplicates: Vec<String>,
+    /// Rows that could not be read and were skipped, with the reason.
+    pub malformed: Vec<String>,
+    /// Optional columns that were not in the roster, so were left blank.
+    pub missing_columns: Vec<String>,
+}
+
+impl ImportReport {
+    /// A summary of the import for showing to the user.
+    pub fn describe(&self) -> String {
+        let mut lines = vec![format!("Added {} students", self.added.len())];
+        for c in self.missing_columns.iter() {
+            lines.push(format!("No '{}' column, it was left blank", c));
+        }
+        if !self.duplicates.is_empty() {
+            lines.push(format!(
+                "Skipped {} students already added:",
+                self.duplicates.len()
+            ));
+            lines.extend(self.duplicates.iter().map(|d| format!("  {}", d)));
+        }
+        if !self.malformed.is_empty() {
+            lines.push(format!("Skipped {} malformed rows:", self.malformed.len()));
+            lines.extend(self.malformed.iter().map(|m| format!("  {}", m)));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Adds every student in a CSV roster to the assignment. Students already in
+/// the assignment are skipped, so a roster can be imported again to add late
+/// enrolments. A student is already there if a student has the same number,
+/// or for rows without a number, the same name. Existing students and their
+/// marks are never changed.
+/// Returns an error if the roster has no header row or no name column.
+pub fn import(
+    assignment: &mut Assignment,
+    text: &str,
+    columns: &RosterColumns,
+) -> Result<ImportReport, String> {
+    let mut reader = ReaderBuilder::new()
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(text.as_bytes());
+    let header = reader.headers().map_err(|e| e.to_string())?.clone();
+    let mut report = ImportReport::default();
+
+    let name_col = match find_column(&header, &columns.name) {
+        Some(c) => c,
+        None => return Err(format!("the roster has no '{}' column", columns.name)),
+    };
+    let mut optional_col = |name: &str| {
+        let col = find_column(&header, name);
+        if col.is_none() && !name.is_empty() {
+            report.missing_columns.push(name.to_string());
+        }
+        col
+    };
+    let number_col = optional_col(&columns.number);
+    let email_col = optional_col(&columns.email);
+    let section_col = optional_col(&columns.section);
+
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                report.malformed.push(e.to_string());
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |p| p.line());
+        if record.len() != header.len() {
+            report.malformed.push(format!(
+                "line {}: has {} fields, expected {}",
+                line,
+                record.len(),
+                header.len()
+            ));
+            continue;
+        }
+
+        let field = |col: Option<usize>| col.map_or("", |c| &record[c]).to_string();
+        let mut student = Student::new(&field(Some(name_col)));
+        student.number = field(number_col);
+        student.email = field(email_col);
+        student.section = field(section_col);
+
+        if student.name.is_empty() {
+            report.malformed.push(format!("line {}: has no name", line));
+        } else if already_added(assignment, &student) {
+            report
+                .duplicates
+                .push(format!("line {}: {}", line, student.label()));
+        } else {
+            report.added.push(student.name.clone());
+            assignment.add_student(student);
+        }
+    }
+    Ok(report)
+}
+
+// Finds the index of the column with the given header.
+fn find_column(header: &StringRecord, name: &str) -> Option<usize> {
+    if name.trim().is_empty() {
+        return None;
+    }
+    header
+        .iter()
+        .position(|h| h.eq_ignore_ascii_case(name.trim()))
+}
+
+// True if the student matches one already in the assignment.
+fn already_added(assignment: &Assignment, student: &Student) -> bool {
+    if student.number.is_empty() {
+        assignment.name_exists(&student.name)
+    } else {
+        assignment.number_exists(&student.number)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ROSTER: &str = "Name,Student Number,Email,Section\n\
+                          Issac Newton,0001,inewton@uni.ca,A01\n\
+                          Albert Einstein,0002,aeinstein@uni.ca,A02\n";
+
+    fn make_test_asn() -> Assignment {
+        Assignment::new("Assignment 5".to_string(), "CS 1000".to_string())
+    }
+
+    #[test]
+    fn it_imports_every_student_in_a_roster() {
+        let mut asn = make_test_asn();
+        let report = import(&mut asn, ROSTER, &RosterColumns::default()).unwrap();
+
+        assert_eq!(report.added, vec!["Issac Newton", "Albert Einstein"]);
+        assert_eq!(asn.num_students(), 2);
+        let einstein = asn.get_student_at(1);
+        assert_eq!(einstein.number, "0002");
+        assert_eq!(einstein.email, "aeinstein@uni.ca");
+        assert_eq!(einstein.section, "A02");
+    }
+
+    #[test]
+    fn it_maps_columns_by_their_headers() {
+        let roster = "Section, SURNAME FIRST ,ID\nA01,\"Newton, Issac\",0001\n";
+        let columns = RosterColumns {
+            name: "Surname First".to_string(),
+            number: "ID".to_string(),
+            email: String::new(),
+            section: "Section".to_string(),
+        };
+        let mut asn = make_test_asn();
+        let report = import(&mut asn, roster, &columns).unwrap();
+
+        assert!(report.missing_columns.is_empty());
+        let newton = asn.get_student_at(0);
+        assert_eq!(newton.name, "Newton, Issac");
+        assert_eq!(newton.number, "0001");
+        assert_eq!(newton.section, "A01");
+    }
+
+    #[test]
+    fn it_merges_late_enrolments_without_changing_existing_students() {
+        let mut asn = make_test_asn();
+        import(&mut asn, ROSTER, &RosterColumns::default()).unwrap();
+        let late = format!("{}Marie Currie,0003,mcurrie@uni.ca,A01\n", ROSTER);
+        let report = import(&mut asn, &late, &RosterColumns::default()).unwrap();
+
+        assert_eq!(report.added, vec!["Marie Currie"]);
+        assert_eq!(report.duplicates.len(), 2);
+        assert_eq!(asn.num_students(), 3);
+        assert_eq!(asn.get_student_at(0).id, 0);
+    }
+
+    #[test]
+    fn it_reports_duplicate_and_malformed_rows() {
+        let roster = "Name,Student Number\n\
+                      Issac Newton,0001\n\
+                      Isaac Newton,0001\n\
+                      ,0002\n\
+                      Marie Currie\n\
+                      Albert Einstein,0004\n";
+        let mut asn = make_test_asn();
+        let report = import(&mut asn, roster, &RosterColumns::default()).unwrap();
+
+        assert_eq!(report.added, vec!["Issac Newton", "Albert Einstein"]);
+        assert_eq!(report.duplicates, vec!["line 3: Isaac Newton (0001)"]);
+        assert_eq!(
+            report.malformed,
+            vec!["line 4: has no name", "line 5: has 1 fields, expected 2"]
+        );
+        assert_eq!(report.missing_columns, vec!["Email", "Section"]);
+    }
+
+    #[test]
+    fn it_needs_a_name_column() {
+        let mut asn = make_test_asn();
+        assert!(import(&mut asn, "Student,Email\n", &RosterColumns::default()).is_err());
+        assert_eq!(asn.num_students(), 0);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::data::stats::Stats;
+use crate::data::Assignment;
+use serde::{Deserialize, Serialize};
+
+/// A step that adjusts every student's total, such as a curve. An
+/// assignment's scaling is a list of steps applied in order to the totals
+/// when they are exported. The marks on each question part are not changed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Scaling {
+    /// Multiplies every total by the same factor so the class mean becomes
+    /// the target.
+    ToMean(f32),
+    /// Adds a constant to every total. It can be negative, but totals never
+    /// go below zero.
+    Add(f32),
+    /// Caps every total at a maximum.
+    Cap(f32),
+    /// Takes the square root of each total as a fraction of the out of, so
+    /// low totals are raised the most and full marks stay the same.
+    SquareRoot,
+    /// Maps totals proportionally onto a different out of.
+    OutOf(u32),
+}
+
+impl Scaling {
+    /// Applies the step to every total in place.
+    /// Returns what the totals are out of after the step.
+    pub fn apply(&self, totals: &mut [f32], out_of: u32) -> u32 {
+        match *self {
+            Scaling::ToMean(target) => {
+                let mean = totals.iter().sum::<f32>() / totals.len().max(1) as f32;
+                if mean > 0.0 {
+                    totals.iter_mut().for_each(|t| *t *= target / mean);
+                }
+                out_of
+            }
+            Scaling::Add(amount) => {
+                totals.iter_mut().for_each(|t| *t = (*t + amount).max(0.0));
+                out_of
+            }
+            Scaling::Cap(max) => {
+                totals.iter_mut().for_each(|t| *t = t.min(max));
+                out_of
+            }
+            Scaling::SquareRoot => {
+                if out_of > 0 {
+                    let out_of = out_of as f32;
+                    totals
+                        .iter_mut()
+                        .for_each(|t| *t = out_of * (t.max(0.0) / out_of).sqrt());
+                }
+                out_of
+            }
+            Scaling::OutOf(new_out_of) => {
+                if out_of > 0 {
+                    let factor = new_out_of as f32 / out_of as f32;
+                    totals.iter_mut().for_each(|t| *t *= factor);
+                }
+                new_out_of
+            }
+        }
+    }
+
+    /// A short description of the step for showing to the user.
+    pub fn describe(&self) -> String {
+        match self {
+            Scaling::ToMean(target) => format!("Scale To Mean Of {}", target),
+            Scaling::Add(amount) => format!("Add {}", amount),
+            Scaling::Cap(max) => format!("Cap At {}", max),
+            Scaling::SquareRoot => "Square Root Curve".to_string(),
+            Scaling::OutOf(out_of) => format!("Map Onto Out Of {}", out_of),
+        }
+    }
+}
+
+/// Applies the steps in order to a class's totals, rounding the results to
+/// two decimal places.
+/// Returns the scaled totals and what they are out of.
+pub fn scale(steps: &[Scaling], totals: &[f32], out_of: u32) -> (Vec<f32>, u32) {
+    let mut scaled = totals.to_vec();
+    let out_of = steps
+        .iter()
+        .fold(out_of, |out_of, step| step.apply(&mut scaled, out_of));
+    (scaled.into_iter().map(round).collect(), out_of)
+}
+
+/// A plain text table of every student's total before and after the steps,
+/// with the class mean, minimum and maximum, so the effect of the steps can
+/// be seen before they are used.
+pub fn convert_preview(assignment: &Assignment, steps: &[Scaling]) -> String {
+    let students = assignment.get_students();
+    let raw: Vec<f32> = students
+        .iter()
+        .map(|s| assignment.students_total(s.id))
+        .collect();
+    let (scaled, scaled_out_of) = scale(steps, &raw, assignment.out_of());
+
+    let mut lines = vec![format!(
+        "{} - {} Scaling Preview",
+        assignment.course, assignment.title
+    )];
+    if steps.is_empty() {
+        lines.push("No scaling".to_string());
+    }
+    for (i, step) in steps.iter().enumerate() {
+        lines.push(format!("{}. {}", i + 1, step.describe()));
+    }
+    lines.push(String::new());
+
+    let width = students.iter().map(|s| s.name.len()).max().unwrap_or(0);
+    let width = width.max("Student".len());
+    lines.push(format!(
+        "{:<width$}  {:>8}  {:>8}",
+        "Student",
+        format!("/{}", assignment.out_of()),
+        format!("/{}", scaled_out_of),
+        width = width
+    ));
+    for (s, (before, after)) in students.iter().zip(raw.iter().zip(scaled.iter())) {
+        lines.push(format!(
+            "{:<width$}  {:>8}  {:>8}",
+            s.name,
+            round(*before),
+            after,
+            width = width
+        ));
+    }
+    let before = Stats::of("Before", &raw, assignment.out_of());
+    let after = Stats::of("After", &scaled, scaled_out_of);
+    if let (Some(before), Some(after)) = (before, after) {
+        lines.push(String::new());
+        let summaries = [
+            ("Mean", before.mean, after.mean),
+            ("Min", before.min, after.min),
+            ("Max", before.max, after.max),
+        ];
+        for (label, before, after) in summaries.iter() {
+            lines.push(format!(
+                "{:<width$}  {:>8}  {:>8}",
+                label,
+                round(*before),
+                round(*after),
+                width = width
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+// Rounds to two decimal places.
+fn round(x: f32) -> f32 {
+    (x * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::{Question, Student};
+
+    const TOTALS: [f32; 3] = [25.0, 50.0, 75.0];
+
+    #[test]
+    fn it_scales_linearly_to_a_target_mean() {
+        let (scaled, out_of) = scale(&[Scaling::ToMean(60.0)], &TOTALS, 100);
+        assert_eq!(scaled, vec![30.0, 60.0, 90.0]);
+        assert_eq!(out_of, 100);
+        assert_eq!(scale(&[Scaling::ToMean(60.0)], &[0.0], 100).0, vec![0.0]);
+    }
+
+    #[test]
+    fn it_adds_a_constant_and_caps() {
+        let steps = [Scaling::Add(30.0), Scaling::Cap(100.0)];
+        assert_eq!(scale(&steps, &TOTALS, 100).0, vec![55.0, 80.0, 100.0]);
+        let (scaled, _) = scale(&[Scaling::Add(-30.0)], &TOTALS, 100);
+        assert_eq!(scaled, vec![0.0, 20.0, 45.0]);
+    }
+
+    #[test]
+    fn it_curves_by_square_root() {
+        let (scaled, _) = scale(&[Scaling::SquareRoot], &[25.0, 100.0, 0.0], 100);
+        assert_eq!(scaled, vec![50.0, 100.0, 0.0]);
+    }
+
+    #[test]
+    fn it_maps_onto_a_different_out_of() {
+        let steps = [Scaling::OutOf(20), Scaling::SquareRoot];
+        let (scaled, out_of) = scale(&steps, &[5.0, 100.0], 100);
+        assert_eq!(scaled, vec![4.47, 20.0]);
+        assert_eq!(out_of, 20);
+    }
+
+    #[test]
+    fn it_previews_totals_before_and_after() {
+        let q = Question::new(1, 1, 10);
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q.num, q.part, q.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        asn.add_student(Student::new("Albert Einstein"));
+        asn.add_comment(newton, &q, 6.0, "Try harder".to_string());
+
+        let preview = convert_preview(&asn, &[Scaling::OutOf(100)]);
+        assert!(preview.contains("1. Map Onto Out Of 100\n"));
+        assert!(preview.contains("Student               /10      /100\n"));
+        assert!(preview.contains("Issac Newton            4        40\n"));
+        assert!(preview.contains("Mean                    7        70\n"));
+    }
+}
@@ -0,0 +1,104 @@
+/// Scores how well a query fuzzy matches a candidate, ignoring case.
+/// The characters of the query must all appear in the candidate in order,
+/// but not necessarily next to each other. Characters that follow on from
+/// the last match or start a word score higher, and gaps between matches
+/// score lower.
+/// Returns None if the candidate does not match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+    for q in query.iter() {
+        let idx = next + candidate[next..].iter().position(|c| c == q)?;
+        score += 1;
+        if idx == 0 || !candidate[idx - 1].is_alphanumeric() {
+            score += 3;
+        }
+        match last_match {
+            Some(last) if last + 1 == idx => score += 5,
+            Some(last) => score -= (idx - last - 1) as i64,
+            None => {}
+        }
+        last_match = Some(idx);
+        next = idx + 1;
+    }
+    Some(score)
+}
+
+/// The indices of the candidates that match the query, best match first.
+/// Exact matches always come first, then ties go to the shortest candidate
+/// and then to the one earliest in the list.
+pub fn rank(query: &str, candidates: &[String]) -> Vec<usize> {
+    let mut matches: Vec<(i64, usize)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| {
+            let score = fuzzy_score(query, c)?;
+            if c.eq_ignore_ascii_case(query.trim()) {
+                Some((i64::MAX, i))
+            } else {
+                Some((score, i))
+            }
+        })
+        .collect();
+    matches.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then(candidates[*a].len().cmp(&candidates[*b].len()))
+            .then(a.cmp(b))
+    });
+    matches.into_iter().map(|(_, i)| i).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn candidates() -> Vec<String> {
+        [
+            "Issac Newton (0001)",
+            "Albert Einstein",
+            "Marie Currie (0003)",
+        ]
+        .iter()
+        .map(|c| c.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn it_matches_characters_in_order_ignoring_case() {
+        assert!(fuzzy_score("newton", "Issac Newton").is_some());
+        assert!(fuzzy_score("isnw", "Issac Newton").is_some());
+        assert!(fuzzy_score("wen", "Issac Newton").is_none());
+        assert!(fuzzy_score("", "Issac Newton").is_some());
+    }
+
+    #[test]
+    fn it_scores_word_starts_and_runs_higher() {
+        let start = fuzzy_score("ne", "Issac Newton").unwrap();
+        let middle = fuzzy_score("ew", "Issac Newton").unwrap();
+        let spread = fuzzy_score("in", "Issac Newton").unwrap();
+        assert!(start > middle);
+        assert!(middle > spread);
+    }
+
+    #[test]
+    fn it_ranks_the_best_matches_first() {
+        assert_eq!(rank("cur", &candidates()), vec![2]);
+        assert_eq!(rank("0001", &candidates()), vec![0]);
+        assert_eq!(rank("e", &candidates()), vec![1, 0, 2]);
+        assert!(rank("xyz", &candidates()).is_empty());
+    }
+
+    #[test]
+    fn it_ranks_exact_matches_first() {
+        let labels: Vec<String> = ["1.12", "1.1", "2.1"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(rank("1.1", &labels), vec![1, 0]);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::data::{gradebook, latex, Assignment};
+
+/// The number of bars in a histogram, each covering the same share of the
+/// marks a question is out of.
+pub const BINS: usize = 10;
+
+/// Summary statistics of the marks students got on a question part or on the
+/// whole assignment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    /// The question part, like 1.2, or Total for the whole assignment.
+    pub label: String,
+    pub out_of: u32,
+    pub count: usize,
+    pub mean: f32,
+    pub median: f32,
+    /// The population standard deviation.
+    pub std_dev: f32,
+    pub min: f32,
+    pub max: f32,
+    /// The number of students with a mark in each tenth of the out of. Marks
+    /// at or over the out of go in the last bin.
+    pub histogram: [u32; BINS],
+}
+
+impl Stats {
+    /// Computes the statistics of some marks out of the given amount.
+    /// Returns None if there are no marks.
+    pub fn of(label: &str, marks: &[f32], out_of: u32) -> Option<Stats> {
+        if marks.is_empty() {
+            return None;
+        }
+        let count = marks.len();
+        let mut sorted = marks.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mean = sorted.iter().sum::<f32>() / count as f32;
+        let median = if count.is_multiple_of(2) {
+            (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+        } else {
+            sorted[count / 2]
+        };
+        let variance = sorted.iter().map(|m| (m - mean).powi(2)).sum::<f32>() / count as f32;
+
+        let mut histogram = [0; BINS];
+        for m in sorted.iter() {
+            let bin = if out_of == 0 {
+                BINS - 1
+            } else {
+                ((m / out_of as f32 * BINS as f32).floor().max(0.0) as usize).min(BINS - 1)
+            };
+            histogram[bin] += 1;
+        }
+
+        Some(Stats {
+            label: label.to_string(),
+            out_of,
+            count,
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            max: sorted[count - 1],
+            histogram,
+        })
+    }
+}
+
+/// The statistics for every question part, followed by those for the total,
+/// over the students being graded. Empty if there are no students.
+pub fn assignment_stats(assignment: &Assignment) -> Vec<Stats> {
+    let students = assignment.get_students();
+    let mut stats: Vec<Stats> = assignment
+        .get_questions()
+        .iter()
+        .filter_map(|q| {
+            let marks: Vec<f32> = students
+                .iter()
+                .map(|s| assignment.students_mark_for(s.id, q))
+                .collect();
+            Stats::of(&q.label(), &marks, q.out_of)
+        })
+        .collect();
+
+    let totals: Vec<f32> = students
+        .iter()
+        .map(|s| assignment.students_total(s.id))
+        .collect();
+    stats.extend(Stats::of("Total", &totals, assignment.out_of()));
+    stats
+}
+
+/// A plain text report of the statistics with a histogram for each part.
+pub fn convert_text(assignment: &Assignment) -> String {
+    let mut lines = vec![format!(
+        "{} - {} Statistics ({} students)",
+        assignment.course,
+        assignment.title,
+        assignment.num_students()
+    )];
+    for s in assignment_stats(assignment).iter() {
+        lines.push(String::new());
+        lines.push(format!("{} (out of {})", s.label, s.out_of));
+        lines.push(format!(
+            "Mean {}  Median {}  Std Dev {}  Min {}  Max {}",
+            round(s.mean),
+            round(s.median),
+            round(s.std_dev),
+            round(s.min),
+            round(s.max)
+        ));
+        for (i, count) in s.histogram.iter().enumerate().rev() {
+            lines.push(format!(
+                "{:>8} | {}{}",
+                bin_label(i),
+                "#".repeat(*count as usize),
+                if *count > 0 {
+                    format!(" {}", count)
+                } else {
+                    String::new()
+                }
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Converts the statistics to CSV with a row for each question part and the
+/// total, and a column for each histogram bin.
+pub fn convert_csv(assignment: &Assignment) -> String {
+    let mut header: Vec<String> = [
+        "Question", "Out Of", "Count", "Mean", "Median", "Std Dev", "Min", "Max",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect();
+    header.extend((0..BINS).map(bin_label));
+
+    let mut rows = vec![header];
+    for s in assignment_stats(assignment) {
+        let mut row = vec![
+            s.label.clone(),
+            s.out_of.to_string(),
+            s.count.to_string(),
+            round(s.mean).to_string(),
+            round(s.median).to_string(),
+            round(s.std_dev).to_string(),
+            round(s.min).to_string(),
+            round(s.max).to_string(),
+        ];
+        row.extend(s.histogram.iter().map(|c| c.to_string()));
+        rows.push(row);
+    }
+    gradebook::write_rows(rows)
+}
+
+/// Converts the statistics to a LaTeX document with a table of the
+/// statistics and a table of the histograms.
+pub fn convert_latex(assignment: &Assignment) -> String {
+    let stats = assignment_stats(assignment);
+    let mut lines = vec![
+        "\\documentclass{article}".to_string(),
+        "\\usepackage{fullpage}".to_string(),
+        format!(
+            "\\title{{{} {} Statistics}}",
+            latex::escape(&assignment.course),
+            latex::escape(&assignment.title)
+        ),
+        format!("\\author{{{} students}}", assignment.num_students()),
+        "\\date{\\today}".to_string(),
+        "\\begin{document}".to_string(),
+        "\\maketitle".to_string(),
+        "\\section*{Summary}".to_string(),
+        "\\begin{tabular}{lrrrrrr}".to_string(),
+        "Question & Out Of & Mean & Median & Std Dev & Min & Max \\\\".to_string(),
+        "\\hline".to_string(),
+    ];
+    for s in stats.iter() {
+        lines.push(format!(
+            "{} & {} & {} & {} & {} & {} & {} \\\\",
+            s.label,
+            s.out_of,
+            round(s.mean),
+            round(s.median),
+            round(s.std_dev),
+            round(s.min),
+            round(s.max)
+        ));
+    }
+    lines.push("\\end{tabular}".to_string());
+
+    lines.push("\\section*{Histograms}".to_string());
+    lines.push(format!("\\begin{{tabular}}{{l{}}}", "r".repeat(BINS)));
+    let bins: Vec<String> = (0..BINS)
+        .map(|i| bin_label(i).replace("%", "\\%"))
+        .collect();
+    lines.push(format!("Question & {} \\\\", bins.join(" & ")));
+    lines.push("\\hline".to_string());
+    for s in stats.iter() {
+        let counts: Vec<String> = s.histogram.iter().map(|c| c.to_string()).collect();
+        lines.push(format!("{} & {} \\\\", s.label, counts.join(" & ")));
+    }
+    lines.push("\\end{tabular}".to_string());
+    lines.push("\\end{document}".to_string());
+    lines.join("\n")
+}
+
+// The range of percentages covered by a histogram bin.
+fn bin_label(bin: usize) -> String {
+    let width = 100 / BINS;
+    if bin == BINS - 1 {
+        format!("{}-100%", bin * width)
+    } else {
+        format!("{}-{}%", bin * width, (bin + 1) * width - 1)
+    }
+}
+
+// Rounds to two decimal places for showing.
+fn round(x: f32) -> f32 {
+    (x * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::{Question, Student};
+
+    fn make_test_asn() -> Assignment {
+        let q1 = Question::new(1, 1, 5);
+        let q2 = Question::new(1, 2, 10);
+
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        let einstein = asn.add_student(Student::new("Albert Einstein"));
+        asn.add_student(Student::new("Marie Currie"));
+
+        asn.add_comment(newton, &q1, 5.0, "Missing".to_string());
+        asn.add_comment(einstein, &q1, 1.0, "Try harder".to_string());
+        asn.add_comment(einstein, &q2, 4.5, "Mind the apples".to_string());
+        asn
+    }
+
+    #[test]
+    fn it_computes_summary_statistics() {
+        let stats = Stats::of("1.1", &[4.0, 0.0, 5.0, 3.0], 5).unwrap();
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.mean, 3.0);
+        assert_eq!(stats.median, 3.5);
+        assert_eq!(round(stats.std_dev), 1.87);
+        assert_eq!(stats.min, 0.0);
+        assert_eq!(stats.max, 5.0);
+        assert_eq!(stats.histogram, [1, 0, 0, 0, 0, 0, 1, 0, 1, 1]);
+        assert_eq!(Stats::of("1.1", &[], 5), None);
+    }
+
+    #[test]
+    fn it_puts_full_and_bonus_marks_in_the_last_bin() {
+        let stats = Stats::of("1.1", &[10.0, 11.0, 9.9], 10).unwrap();
+        assert_eq!(stats.histogram[BINS - 1], 3);
+    }
+
+    #[test]
+    fn it_computes_stats_for_each_question_and_the_total() {
+        let stats = assignment_stats(&make_test_asn());
+        let labels: Vec<&str> = stats.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["1.1", "1.2", "Total"]);
+        assert_eq!(stats[0].mean, 3.0);
+        assert_eq!(stats[1].median, 10.0);
+        assert_eq!(stats[2].min, 9.5);
+        assert_eq!(stats[2].out_of, 15);
+    }
+
+    #[test]
+    fn it_converts_stats_to_csv() {
+        let csv = convert_csv(&make_test_asn());
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "Question,Out Of,Count,Mean,Median,Std Dev,Min,Max,0-9%,10-19%,20-29%,30-39%,\
+             40-49%,50-59%,60-69%,70-79%,80-89%,90-100%"
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "1.1,5,3,3,4,2.16,0,5,1,0,0,0,0,0,0,0,1,1"
+        );
+    }
+
+    #[test]
+    fn it_draws_text_histograms() {
+        let text = convert_text(&make_test_asn());
+        assert!(text.contains("Mean 3  Median 4  Std Dev 2.16  Min 0  Max 5"));
+        assert!(text.contains(" 90-100% | ## 2\n"));
+        assert!(text.contains("   0-9% | # 1\n"));
+    }
+}
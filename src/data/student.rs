@@ -0,0 +1,125 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// A student being graded on an assignment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Student {
+    /// A unique id given by the assignment when the student is added. It never
+    /// changes, so comments refer to students by it rather than by name.
+    pub id: u64,
+    /// The name shown on grade sheets.
+    pub name: String,
+    /// The student number given by the school. Empty if it is not known.
+    #[serde(default)]
+    pub number: String,
+    /// The student's email address. Empty if it is not known.
+    #[serde(default)]
+    pub email: String,
+    /// The course section the student is in. Empty if it is not known.
+    #[serde(default)]
+    pub section: String,
+    /// True if the student has withdrawn. They keep their marks, but are left
+    /// out of grading and exports unless they are reinstated.
+    #[serde(default)]
+    pub withdrawn: bool,
+    /// When the student submitted the assignment. None if it is not known,
+    /// in which case they are not treated as late.
+    #[serde(default)]
+    pub submitted: Option<NaiveDateTime>,
+    /// The student's own due date if they were given an extension.
+    #[serde(default)]
+    pub extension: Option<NaiveDateTime>,
+}
+
+impl Student {
+    /// A new student with only a name. The id is set by the assignment when
+    /// the student is added to it.
+    pub fn new(name: &str) -> Student {
+        Student {
+            id: 0,
+            name: name.to_string(),
+            number: String::new(),
+            email: String::new(),
+            section: String::new(),
+            withdrawn: false,
+            submitted: None,
+            extension: None,
+        }
+    }
+
+    /// The best value for identifying the student outside of easy-mark.
+    /// Their student number if it is known, then their email, then their name.
+    pub fn identifier(&self) -> &str {
+        if !self.number.is_empty() {
+            &self.number
+        } else if !self.email.is_empty() {
+            &self.email
+        } else {
+            &self.name
+        }
+    }
+
+    /// The student's name, followed by their number if it is known, for
+    /// telling apart students with the same name.
+    pub fn label(&self) -> String {
+        if self.number.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, self.number)
+        }
+    }
+
+    /// The start of the names of files written for the student. It is their
+    /// name, followed by their student number if it is known, or else their
+    /// id, so students with the same name get different files.
+    pub fn file_stem(&self) -> String {
+        let stem = if self.number.is_empty() {
+            format!("{} id{}", self.name, self.id)
+        } else {
+            format!("{} {}", self.name, self.number)
+        };
+        stem.replace(" ", "_")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::Assignment;
+
+    #[test]
+    fn it_identifies_by_number_then_email_then_name() {
+        let mut student = Student::new("Marie Currie");
+        assert_eq!(student.identifier(), "Marie Currie");
+        student.email = "mcurrie@uni.ca".to_string();
+        assert_eq!(student.identifier(), "mcurrie@uni.ca");
+        student.number = "001234".to_string();
+        assert_eq!(student.identifier(), "001234");
+    }
+
+    #[test]
+    fn it_labels_students_with_their_number() {
+        let mut student = Student::new("Marie Currie");
+        assert_eq!(student.label(), "Marie Currie");
+        student.number = "001234".to_string();
+        assert_eq!(student.label(), "Marie Currie (001234)");
+    }
+
+    #[test]
+    fn it_makes_file_names_unique_with_the_student_number() {
+        let mut student = Student::new("Marie Currie");
+        student.number = "001234".to_string();
+        assert_eq!(student.file_stem(), "Marie_Currie_001234");
+    }
+
+    #[test]
+    fn it_makes_file_names_unique_with_the_id_when_there_is_no_number() {
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        let first = asn.add_student(Student::new("Marie Currie"));
+        let second = asn.add_student(Student::new("Marie Currie"));
+        let first = asn.get_student(first).file_stem();
+        let second = asn.get_student(second).file_stem();
+        assert_eq!(first, "Marie_Currie_id0");
+        assert_eq!(second, "Marie_Currie_id1");
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+
+/// A value that can be put into a template.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Text put in place of a `{{name}}` tag.
+    Text(String),
+    /// Whether a `{{#name}}` section is shown, or a `{{^name}}` one is not.
+    Bool(bool),
+    /// A `{{#name}}` section is shown once for each item, with the item's
+    /// values added to the ones already there. An empty list is false.
+    List(Vec<Context>),
+}
+
+/// The named values a template is filled in with.
+pub type Context = BTreeMap<String, Value>;
+
+/// A template for a text document, in a small subset of Mustache:
+///
+/// - `{{name}}` is replaced by the text value `name`.
+/// - `{{#name}}...{{/name}}` is shown for each item of the list `name`, or
+///   once if `name` is true.
+/// - `{{^name}}...{{/name}}` is shown only if `name` is false or an empty
+///   list.
+///
+/// A line holding only a section tag is left out of the output entirely.
+/// Braces right before a tag are kept as text, so LaTeX like
+/// `\title{{{course}}}` puts the course inside the title's braces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var(String),
+    Section {
+        name: String,
+        inverted: bool,
+        nodes: Vec<Node>,
+    },
+}
+
+impl Template {
+    /// Parses a template.
+    /// Returns an error if a tag is not closed, or a section is not closed or
+    /// is closed out of order.
+    pub fn parse(text: &str) -> Result<Template, String> {
+        let mut stack: Vec<(String, bool, Vec<Node>)> = Vec::new();
+        let mut nodes = Vec::new();
+        for (line_num, line) in split_lines(text).into_iter().enumerate() {
+            let tags = parse_line(line).map_err(|e| format!("line {}: {}", line_num + 1, e))?;
+            let standalone = tags.iter().all(|t| match t {
+                Tag::Text(text) => text.trim().is_empty(),
+                Tag::Var(_) => false,
+                _ => true,
+            }) && tags.iter().any(|t| !matches!(t, Tag::Text(_)));
+
+            for tag in tags {
+                match tag {
+                    Tag::Text(_) if standalone => {}
+                    Tag::Text(text) => nodes.push(Node::Text(text)),
+                    Tag::Var(name) => nodes.push(Node::Var(name)),
+                    Tag::Open(name, inverted) => {
+                        stack.push((name, inverted, nodes));
+                        nodes = Vec::new();
+                    }
+                    Tag::Close(name) => match stack.pop() {
+                        Some((open, inverted, mut outer)) if open == name => {
+                            outer.push(Node::Section {
+                                name,
+                                inverted,
+                                nodes,
+                            });
+                            nodes = outer;
+                        }
+                        Some((open, _, _)) => {
+                            return Err(format!(
+                                "line {}: {{{{/{}}}}} closes {{{{#{}}}}}",
+                                line_num + 1,
+                                name,
+                                open
+                            ))
+                        }
+                        None => {
+                            return Err(format!(
+                                "line {}: {{{{/{}}}}} has no section to close",
+                                line_num + 1,
+                                name
+                            ))
+                        }
+                    },
+                }
+            }
+        }
+        match stack.pop() {
+            Some((name, _, _)) => Err(format!("section {{{{#{}}}}} is not closed", name)),
+            None => Ok(Template { nodes }),
+        }
+    }
+
+    /// Fills in the template with the values in the context.
+    /// Returns an error if the template uses a name that is not in the
+    /// context, or uses a value in the wrong way, like a list as text.
+    pub fn render(&self, context: &Context) -> Result<String, String> {
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut vec![context], &mut out)?;
+        Ok(out)
+    }
+}
+
+// A piece of a single line of a template.
+enum Tag {
+    Text(String),
+    Var(String),
+    Open(String, bool),
+    Close(String),
+}
+
+// Splits text into lines that keep their line endings.
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn parse_line(mut line: &str) -> Result<Vec<Tag>, String> {
+    let mut tags = Vec::new();
+    while let Some(mut start) = line.find("{{") {
+        // Extra opening braces belong to the text before the tag.
+        while line[start + 2..].starts_with('{') {
+            start += 1;
+        }
+        let end = match line[start..].find("}}") {
+            Some(end) => start + end,
+            None => return Err("a tag is not closed with }}".to_string()),
+        };
+        if start > 0 {
+            tags.push(Tag::Text(line[..start].to_string()));
+        }
+        let tag = line[start + 2..end].trim();
+        tags.push(match tag.chars().next() {
+            Some('#') => Tag::Open(tag[1..].trim().to_string(), false),
+            Some('^') => Tag::Open(tag[1..].trim().to_string(), true),
+            Some('/') => Tag::Close(tag[1..].trim().to_string()),
+            Some(_) => Tag::Var(tag.to_string()),
+            None => return Err("a tag has no name".to_string()),
+        });
+        line = &line[end + 2..];
+    }
+    if !line.is_empty() {
+        tags.push(Tag::Text(line.to_string()));
+    }
+    Ok(tags)
+}
+
+// Renders nodes with a stack of contexts, looking names up from the
+// innermost context out.
+fn render_nodes<'a>(
+    nodes: &'a [Node],
+    contexts: &mut Vec<&'a Context>,
+    out: &mut String,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var(name) => match lookup(contexts, name)? {
+                Value::Text(text) => out.push_str(text),
+                _ => return Err(format!("{{{{{}}}}} is not text", name)),
+            },
+            Node::Section {
+                name,
+                inverted,
+                nodes,
+            } => {
+                let value = lookup(contexts, name)?;
+                let shown = match value {
+                    Value::Bool(b) => *b,
+                    Value::List(items) => !items.is_empty(),
+                    Value::Text(_) => {
+                        return Err(format!("{{{{#{}}}}} is not a list or true/false", name))
+                    }
+                };
+                match value {
+                    Value::List(items) if !inverted => {
+                        for item in items.iter() {
+                            contexts.push(item);
+                            render_nodes(nodes, contexts, out)?;
+                            contexts.pop();
+                        }
+                    }
+                    _ if shown != *inverted => render_nodes(nodes, contexts, out)?,
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn lookup<'a>(contexts: &[&'a Context], name: &str) -> Result<&'a Value, String> {
+    contexts
+        .iter()
+        .rev()
+        .find_map(|c| c.get(name))
+        .ok_or_else(|| format!("unknown name '{}'", name))
+}
+
+/// Adds a text value to a context.
+pub fn text(context: &mut Context, name: &str, value: impl ToString) {
+    context.insert(name.to_string(), Value::Text(value.to_string()));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn context() -> Context {
+        let mut ctx = Context::new();
+        text(&mut ctx, "course", "CS 1000");
+        let parts = ["1.1", "1.2"]
+            .iter()
+            .map(|label| {
+                let mut part = Context::new();
+                text(&mut part, "label", label);
+                part
+            })
+            .collect();
+        ctx.insert("parts".to_string(), Value::List(parts));
+        ctx.insert("late".to_string(), Value::Bool(false));
+        ctx
+    }
+
+    fn render(template: &str) -> Result<String, String> {
+        Template::parse(template)?.render(&context())
+    }
+
+    #[test]
+    fn it_fills_in_text_and_keeps_latex_braces() {
+        assert_eq!(
+            render("\\title{{{course}} Grading}").unwrap(),
+            "\\title{CS 1000 Grading}"
+        );
+        assert_eq!(render("{{{course}}}").unwrap(), "{CS 1000}");
+    }
+
+    #[test]
+    fn it_repeats_list_sections_with_outer_names_in_scope() {
+        assert_eq!(
+            render("{{#parts}}{{course}} {{label}};{{/parts}}").unwrap(),
+            "CS 1000 1.1;CS 1000 1.2;"
+        );
+    }
+
+    #[test]
+    fn it_shows_sections_by_truth_and_drops_standalone_tag_lines() {
+        let template = "Start\n  {{#late}}\nLate\n{{/late}}\n{{^late}}\nOn time\n{{/late}}\nEnd";
+        assert_eq!(render(template).unwrap(), "Start\nOn time\nEnd");
+    }
+
+    #[test]
+    fn it_reports_template_mistakes() {
+        assert!(Template::parse("{{#parts}}").is_err());
+        assert!(Template::parse("{{#parts}}{{/late}}").is_err());
+        assert!(Template::parse("{{course").is_err());
+        assert!(render("{{student}}").is_err());
+        assert!(render("{{parts}}").is_err());
+    }
+}
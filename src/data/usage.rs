@@ -0,0 +1,251 @@
+use crate::data::{deduction_label, gradebook, Assignment, Question};
+use std::cmp::Ordering;
+
+/// How comments are ordered within each question part in a usage report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageOrder {
+    /// The order the comments were created in.
+    Created,
+    /// The comments given to the most students first.
+    Students,
+    /// The comments that took the most marks in total first.
+    MarksLost,
+}
+
+impl UsageOrder {
+    /// All of the orders.
+    pub fn all() -> Vec<UsageOrder> {
+        vec![
+            UsageOrder::Created,
+            UsageOrder::Students,
+            UsageOrder::MarksLost,
+        ]
+    }
+
+    /// The name of the order on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UsageOrder::Created => "created",
+            UsageOrder::Students => "students",
+            UsageOrder::MarksLost => "marks",
+        }
+    }
+
+    /// Finds an order by its name, ignoring case.
+    pub fn from_name(name: &str) -> Option<UsageOrder> {
+        let name = name.to_lowercase();
+        UsageOrder::all().into_iter().find(|o| o.name() == name)
+    }
+
+    /// A short description of the order for showing to the user.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            UsageOrder::Created => "Order Created",
+            UsageOrder::Students => "Most Students",
+            UsageOrder::MarksLost => "Most Marks Lost",
+        }
+    }
+}
+
+/// How much a comment was used on a question part.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommentUsage {
+    pub question: Question,
+    pub id: u64,
+    pub text: String,
+    pub deduction: f32,
+    /// The number of students being graded that have the comment.
+    pub students: u32,
+    /// The percentage of the students being graded that have the comment.
+    pub percent: f32,
+    /// The deduction times the number of students. It is negative for a
+    /// bonus. It does not account for marks that could not be lost because
+    /// a student's mark for the question was already zero.
+    pub marks_lost: f32,
+}
+
+/// The usage of every comment, grouped by question part, with the comments
+/// for each part in the given order.
+pub fn comment_usage(assignment: &Assignment, order: UsageOrder) -> Vec<CommentUsage> {
+    let students = assignment.get_students();
+    let mut usage = Vec::new();
+    for q in assignment.get_questions().iter() {
+        let mut part: Vec<CommentUsage> = assignment
+            .comments_for(q)
+            .into_iter()
+            .map(|c| {
+                let count = students.iter().filter(|s| c.has_student(s.id)).count() as u32;
+                CommentUsage {
+                    question: q.clone(),
+                    id: c.id,
+                    percent: if students.is_empty() {
+                        0.0
+                    } else {
+                        count as f32 * 100.0 / students.len() as f32
+                    },
+                    marks_lost: c.deduction * count as f32,
+                    text: c.text,
+                    deduction: c.deduction,
+                    students: count,
+                }
+            })
+            .collect();
+        match order {
+            UsageOrder::Created => {}
+            UsageOrder::Students => part.sort_by(|a, b| {
+                b.students
+                    .cmp(&a.students)
+                    .then(compare_marks(b.marks_lost, a.marks_lost))
+            }),
+            UsageOrder::MarksLost => part.sort_by(|a, b| {
+                compare_marks(b.marks_lost, a.marks_lost).then(b.students.cmp(&a.students))
+            }),
+        }
+        usage.extend(part);
+    }
+    usage
+}
+
+/// A plain text report of the comment usage for each question part.
+pub fn convert_text(assignment: &Assignment, order: UsageOrder) -> String {
+    let mut lines = vec![format!(
+        "{} - {} Comment Usage ({} students, {})",
+        assignment.course,
+        assignment.title,
+        assignment.num_students(),
+        order.describe()
+    )];
+    let mut last: Option<Question> = None;
+    for u in comment_usage(assignment, order) {
+        if !last.is_some_and(|q| q.same_part(&u.question)) {
+            lines.push(String::new());
+            lines.push(format!("Question {}", u.question.label()));
+        }
+        lines.push(format!(
+            "{:>4} students {:>5}%  {:>6} marks  [{}] {}",
+            u.students,
+            round(u.percent),
+            round(u.marks_lost),
+            deduction_label(u.deduction),
+            u.text
+        ));
+        last = Some(u.question);
+    }
+    lines.join("\n")
+}
+
+/// Converts the comment usage to CSV with a row for each comment.
+pub fn convert_csv(assignment: &Assignment, order: UsageOrder) -> String {
+    let header = [
+        "Question",
+        "Comment Id",
+        "Deduction",
+        "Students",
+        "Percent",
+        "Marks Lost",
+        "Text",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect();
+
+    let mut rows = vec![header];
+    for u in comment_usage(assignment, order) {
+        rows.push(vec![
+            u.question.label(),
+            u.id.to_string(),
+            u.deduction.to_string(),
+            u.students.to_string(),
+            round(u.percent).to_string(),
+            u.marks_lost.to_string(),
+            u.text,
+        ]);
+    }
+    gradebook::write_rows(rows)
+}
+
+// Orders marks, which are never NaN.
+fn compare_marks(a: f32, b: f32) -> Ordering {
+    a.partial_cmp(&b).unwrap()
+}
+
+// Rounds to two decimal places for showing.
+fn round(x: f32) -> f32 {
+    (x * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::Student;
+
+    fn make_test_asn() -> Assignment {
+        let q1 = Question::new(1, 1, 5);
+        let q2 = Question::new(1, 2, 5);
+
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        let einstein = asn.add_student(Student::new("Albert Einstein"));
+        let currie = asn.add_student(Student::new("Marie Currie"));
+        asn.add_student(Student::new("Ada Lovelace"));
+
+        asn.add_comment(newton, &q1, 3.0, "Amateurish work".to_string());
+        asn.add_comment(newton, &q1, 0.5, "Show units".to_string());
+        asn.add_to_comment(einstein, &q1, 1);
+        asn.add_to_comment(currie, &q1, 1);
+        asn.add_comment(currie, &q2, -1.0, "Elegant".to_string());
+        asn
+    }
+
+    #[test]
+    fn it_counts_the_students_and_marks_for_each_comment() {
+        let usage = comment_usage(&make_test_asn(), UsageOrder::Created);
+        assert_eq!(usage.len(), 3);
+        assert_eq!(usage[1].text, "Show units");
+        assert_eq!(usage[1].students, 3);
+        assert_eq!(usage[1].percent, 75.0);
+        assert_eq!(usage[1].marks_lost, 1.5);
+        assert_eq!(usage[2].question, Question::new(1, 2, 5));
+        assert_eq!(usage[2].marks_lost, -1.0);
+    }
+
+    #[test]
+    fn it_orders_comments_within_each_question() {
+        let asn = make_test_asn();
+        let ids = |order| -> Vec<u64> { comment_usage(&asn, order).iter().map(|u| u.id).collect() };
+        assert_eq!(ids(UsageOrder::Created), vec![0, 1, 2]);
+        assert_eq!(ids(UsageOrder::Students), vec![1, 0, 2]);
+        assert_eq!(ids(UsageOrder::MarksLost), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn it_leaves_out_withdrawn_students() {
+        let mut asn = make_test_asn();
+        asn.withdraw_student(0);
+        let usage = comment_usage(&asn, UsageOrder::Created);
+        assert_eq!(usage[0].students, 0);
+        assert_eq!(usage[1].students, 2);
+        assert_eq!(round(usage[1].percent), 66.67);
+    }
+
+    #[test]
+    fn it_converts_usage_to_csv() {
+        let csv = convert_csv(&make_test_asn(), UsageOrder::Students);
+        assert_eq!(
+            csv,
+            "Question,Comment Id,Deduction,Students,Percent,Marks Lost,Text\n\
+             1.1,1,0.5,3,75,1.5,Show units\n\
+             1.1,0,3,1,25,3,Amateurish work\n\
+             1.2,2,-1,1,25,-1,Elegant\n"
+        );
+    }
+
+    #[test]
+    fn it_shows_deductions_and_bonuses_with_their_sign() {
+        let text = convert_text(&make_test_asn(), UsageOrder::Created);
+        assert!(text.contains("[-3] Amateurish work\n"));
+        assert!(text.ends_with("[+1] Elegant"));
+    }
+}
@@ -1,61 +1,227 @@
-use crate::data::{latex, Assignment};
-use crate::io::FileOps;
+use crate::data::lms::{self, Lms};
+use crate::data::template::Template;
+use crate::data::usage::{self, UsageOrder};
+use crate::data::{gradebook, stats, Assignment};
+use crate::io::render::GradeSheetRenderer;
+use crate::io::storage::{self, StorageFormat};
+use crate::io::{backup, FileError, FileOps};
+use chrono::Local;
 use std::fs;
-use std::fs::File;
-use std::io::Write;
 
 pub struct AppFileOps;
 
 impl FileOps for AppFileOps {
-    fn load_assignment(filename: &str) -> Assignment {
-        let f = File::open(filename).expect("Unable to open file");
-        serde_pickle::de::from_reader(f).expect("could not pickle")
+    fn load_assignment(filename: &str) -> Result<Assignment, FileError> {
+        let bytes = fs::read(filename).map_err(|e| FileError::reading(filename, e))?;
+        storage::from_bytes(&bytes).map_err(|e| FileError::Corrupt(filename.to_string(), e))
     }
 
-    fn save_assignment(assignment: &Assignment) {
-        let filename =
-            format!("{}_{}.emark", assignment.course, assignment.title).replace(" ", "_");
-        let mut f = File::create(filename).expect("Unable to create file");
-        serde_pickle::ser::to_writer(&mut f, assignment, true).expect("could not pickle");
+    fn save_assignment(assignment: &Assignment, filename: &str) -> Result<(), FileError> {
+        let bytes = storage::to_bytes(assignment, StorageFormat::from_filename(filename))
+            .map_err(|e| FileError::WriteFailed(filename.to_string(), e))?;
+        backup::rotate(filename)?;
+        backup::write_atomic(filename, &bytes)
     }
 
-    fn save_latex_grade_sheet(assignment: &Assignment, student: &String) {
-        let filename = format!(
-            "{}_{}.tex",
-            student.replace(" ", "_"),
-            assignment
-                .students_total(student)
-                .to_string()
-                .replace(".", "_")
-        );
-        let latex = latex::convert_assignment(assignment, student);
-        let mut f = File::create(filename).expect("Could not open file for writing");
-        f.write_all(latex.as_bytes())
-            .expect("Could not write to file");
+    fn load_roster(filename: &str) -> Result<String, FileError> {
+        fs::read_to_string(filename).map_err(|e| FileError::reading(filename, e))
+    }
+
+    fn load_template(filename: &str) -> Result<Template, FileError> {
+        let text = fs::read_to_string(filename).map_err(|e| FileError::reading(filename, e))?;
+        Template::parse(&text).map_err(|e| FileError::InvalidTemplate(filename.to_string(), e))
     }
 
-    fn save_all_latex_grade_sheets(assignment: &Assignment) {
-        let dirname =
-            format!("./{}_{}_latex", assignment.course, assignment.title).replace(" ", "_");
-        if !fs::metadata(&dirname).is_ok() {
-            fs::create_dir(&dirname).expect("Error creating directory for latex files");
+    fn restore_backup(backup_file: &str) -> Result<String, FileError> {
+        let (filename, _) = backup::parse_backup_name(backup_file).ok_or_else(|| {
+            FileError::Corrupt(backup_file.to_string(), "not a backup file".to_string())
+        })?;
+        let bytes = fs::read(backup_file).map_err(|e| FileError::reading(backup_file, e))?;
+        storage::from_bytes(&bytes).map_err(|e| FileError::Corrupt(backup_file.to_string(), e))?;
+
+        // Keep the file being replaced in case the wrong backup was chosen.
+        if fs::metadata(&filename).is_ok() {
+            backup::take_backup(&filename, &Local::now().naive_local())?;
         }
+        backup::write_atomic(&filename, &bytes)?;
+        Ok(filename)
+    }
+
+    fn save_grade_sheet(
+        assignment: &Assignment,
+        student: u64,
+        renderer: &dyn GradeSheetRenderer,
+    ) -> Result<(), FileError> {
+        let filename = sheet_filename(assignment, student, renderer.extension());
+        let template = renderer.template(assignment, &Self::load_template)?;
+        let sheet = render(assignment, student, renderer, &template)?;
+        write_file(&filename, sheet.as_bytes())?;
+        println!("*** Wrote {} ***\n", filename);
+        if renderer.after_writing_step(assignment).is_some() {
+            renderer
+                .after_writing(assignment, &filename)
+                .map_err(|e| FileError::CompileFailed(filename, e))?;
+        }
+        Ok(())
+    }
 
-        println!("==== Writing All Grade Sheets To Latex ====");
+    fn save_all_grade_sheets(
+        assignment: &Assignment,
+        renderer: &dyn GradeSheetRenderer,
+    ) -> Result<(), FileError> {
+        let dirname = sheet_dirname(assignment, renderer.name())?;
+        let template = renderer.template(assignment, &Self::load_template)?;
+        let mut files = Vec::new();
+        println!(
+            "==== Writing All Grade Sheets To {} ====",
+            renderer.describe()
+        );
         for s in assignment.get_students().iter() {
-            let filename = dirname.clone()
-                + "/"
-                + &format!(
-                    "{}_{}.tex",
-                    s.replace(" ", "_"),
-                    assignment.students_total(&s).to_string().replace(".", "_")
-                );
-            let latex = latex::convert_assignment(assignment, &s);
-            let mut f = File::create(filename).expect("Could not open file for writing");
-            f.write_all(latex.as_bytes())
-                .expect("Could not write to file");
-            println!("{}", s);
+            let filename = format!(
+                "{}/{}",
+                dirname,
+                sheet_filename(assignment, s.id, renderer.extension())
+            );
+            let sheet = render(assignment, s.id, renderer, &template)?;
+            write_file(&filename, sheet.as_bytes())?;
+            println!("{}", s.name);
+            files.push((s.name.clone(), filename));
+        }
+        println!();
+
+        match renderer.after_writing_step(assignment) {
+            Some(step) => after_writing_all(assignment, renderer, &step, &dirname, &files),
+            None => Ok(()),
+        }
+    }
+
+    fn save_gradebook(assignment: &Assignment, long: bool) -> Result<(), FileError> {
+        let (suffix, csv) = if long {
+            ("comments", gradebook::convert_comments(assignment))
+        } else {
+            ("grades", gradebook::convert_assignment(assignment))
+        };
+        let filename =
+            format!("{}_{}_{}.csv", assignment.course, assignment.title, suffix).replace(" ", "_");
+        write_file(&filename, csv.as_bytes())?;
+        println!("*** Wrote {} ***\n", filename);
+        Ok(())
+    }
+
+    fn save_lms_grades(assignment: &Assignment, lms: Lms) -> Result<(), FileError> {
+        let csv = lms::convert_assignment(assignment, lms);
+        let filename = format!(
+            "{}_{}_{}.csv",
+            assignment.course,
+            assignment.title,
+            lms.name()
+        )
+        .replace(" ", "_");
+        write_file(&filename, csv.as_bytes())?;
+        println!("*** Wrote {} ***\n", filename);
+        Ok(())
+    }
+
+    fn save_statistics(assignment: &Assignment, latex: bool) -> Result<(), FileError> {
+        let (extension, report) = if latex {
+            ("tex", stats::convert_latex(assignment))
+        } else {
+            ("csv", stats::convert_csv(assignment))
+        };
+        let filename = format!(
+            "{}_{}_stats.{}",
+            assignment.course, assignment.title, extension
+        )
+        .replace(" ", "_");
+        write_file(&filename, report.as_bytes())?;
+        println!("*** Wrote {} ***\n", filename);
+        Ok(())
+    }
+
+    fn save_comment_usage(assignment: &Assignment, order: UsageOrder) -> Result<(), FileError> {
+        let filename = format!(
+            "{}_{}_comment_usage.csv",
+            assignment.course, assignment.title
+        )
+        .replace(" ", "_");
+        write_file(&filename, usage::convert_csv(assignment, order).as_bytes())?;
+        println!("*** Wrote {} ***\n", filename);
+        Ok(())
+    }
+}
+
+// The name of a student's grade sheet file, made from their file stem and
+// total.
+fn sheet_filename(assignment: &Assignment, student: u64, extension: &str) -> String {
+    format!(
+        "{}_{}.{}",
+        assignment.get_student(student).file_stem(),
+        assignment
+            .students_scaled_total(student)
+            .to_string()
+            .replace(".", "_"),
+        extension
+    )
+}
+
+// Creates the directory every student's grade sheets in a format go in, if
+// it is not there already.
+fn sheet_dirname(assignment: &Assignment, format: &str) -> Result<String, FileError> {
+    let dirname =
+        format!("./{}_{}_{}", assignment.course, assignment.title, format).replace(" ", "_");
+    if fs::metadata(&dirname).is_err() {
+        fs::create_dir(&dirname).map_err(|e| FileError::writing(&dirname, e))?;
+    }
+    Ok(dirname)
+}
+
+// Fills in a student's grade sheet, blaming the template if it fails.
+fn render(
+    assignment: &Assignment,
+    student: u64,
+    renderer: &dyn GradeSheetRenderer,
+    (path, template): &(String, Template),
+) -> Result<String, FileError> {
+    renderer
+        .render(assignment, student, template)
+        .map_err(|e| FileError::InvalidTemplate(path.clone(), e))
+}
+
+// Runs the renderer's last step on every student's grade sheet, carrying on
+// past failures so they can all be reported together.
+fn after_writing_all(
+    assignment: &Assignment,
+    renderer: &dyn GradeSheetRenderer,
+    step: &str,
+    dirname: &str,
+    files: &[(String, String)],
+) -> Result<(), FileError> {
+    println!("==== {} ====", step);
+    let mut failed = Vec::new();
+    for (name, filename) in files.iter() {
+        match renderer.after_writing(assignment, filename) {
+            Ok(()) => println!("{}", name),
+            Err(e) => {
+                println!("{} -- FAILED", name);
+                failed.push(format!("{}:\n  {}", name, e.replace("\n", "\n  ")));
+            }
         }
-        println!("");
     }
+    println!();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        let summary = format!(
+            "{} of {} grade sheets failed\n{}",
+            failed.len(),
+            files.len(),
+            failed.join("\n")
+        );
+        Err(FileError::CompileFailed(dirname.to_string(), summary))
+    }
+}
+
+// Writes the bytes to the file, replacing anything already in it.
+fn write_file(filename: &str, bytes: &[u8]) -> Result<(), FileError> {
+    fs::write(filename, bytes).map_err(|e| FileError::writing(filename, e))
 }
@@ -1,5 +1,13 @@
-use crate::data::{Assignment, Question};
-use crate::io::{input, AppFileOps, FileOps, View};
+use crate::data::late::{self, LatePenalty, LatePolicy};
+use crate::data::lms::Lms;
+use crate::data::roster::RosterColumns;
+use crate::data::usage::{self, UsageOrder};
+use crate::data::{deduction_label, Assignment, Question, Scaling, Student};
+use crate::data::{scaling, search, stats};
+use crate::io::render::{self, GradeSheetRenderer};
+use crate::io::{backup, input, storage, FileError, LoadChoice, StorageFormat, View};
+use chrono::NaiveDateTime;
+use itertools::Itertools;
 use std::fs;
 use std::process::Command;
 
@@ -8,6 +16,9 @@ static CLEAR: &str = "clear";
 #[cfg(target_os = "windows")]
 static CLEAR: &str = "cls";
 
+// The most matches shown when a search matches more than one.
+const MAX_MATCHES: usize = 9;
+
 pub struct AppView;
 
 impl View for AppView {
@@ -15,7 +26,15 @@ impl View for AppView {
         Command::new(CLEAR).status().unwrap();
     }
 
-    fn show_menu(header: &str, menu: &Vec<String>) -> u32 {
+    fn show_error(error: &FileError) {
+        println!("\n*** {} ***\n", error);
+    }
+
+    fn show_message(message: &str) {
+        println!("*** {} ***\n", message);
+    }
+
+    fn show_menu(header: &str, menu: &[String]) -> u32 {
         println!("==== {} ====", header);
         for (i, item) in menu.iter().enumerate() {
             println!("{}. {}", i + 1, item);
@@ -31,21 +50,81 @@ impl View for AppView {
         num
     }
 
-    fn new_student(assignment: &Assignment) -> String {
-        print!("==== New Student ====\n");
-        loop {
-            match input::get_line("Student Name: ") {
-                name if assignment.student_exists(&name) => {
-                    println!("\n*** A Student with that name has already been added ***\n")
-                }
-                name => {
-                    Self::clear_screen();
-                    break name;
+    fn new_student(assignment: &Assignment) -> Student {
+        println!("==== New Student ====");
+        let mut student = Student::new(&input::get_line("Student Name: "));
+        student.number = loop {
+            match input::get_optional_line("Student Number (optional): ") {
+                number if !number.is_empty() && assignment.number_exists(&number) => {
+                    println!("\n*** A Student with that number has already been added ***\n")
                 }
+                number => break number,
             }
+        };
+        student.email = input::get_optional_line("Email (optional): ");
+        student.section = input::get_optional_line("Section (optional): ");
+        Self::clear_screen();
+        student
+    }
+
+    fn rename_student(student: &Student) -> Option<String> {
+        println!("==== Rename Student ====");
+        let name = input::readline_with_initial("Student Name: ", (&student.name, ""));
+        Self::clear_screen();
+        if name == student.name {
+            None
+        } else {
+            Some(name)
         }
     }
 
+    fn choose_student(students: &[Student], header: &str) -> Option<u64> {
+        let mut menu: Vec<String> = students.iter().map(|s| s.label()).collect();
+        menu.push("Cancel".to_string());
+
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
+        students.get(choice).map(|s| s.id)
+    }
+
+    fn go_to_student(assignment: &Assignment) -> Option<u64> {
+        let students = assignment.get_students();
+        let labels: Vec<String> = students.iter().map(|s| s.label()).collect();
+        choose_match("Go To Student", &labels).map(|i| students[i].id)
+    }
+
+    fn go_to_question(assignment: &Assignment) -> Option<Question> {
+        let questions = assignment.get_questions();
+        let labels: Vec<String> = questions.iter().map(|q| q.label()).collect();
+        choose_match("Go To Question", &labels).map(|i| questions[i].clone())
+    }
+
+    fn choose_roster() -> Option<(String, RosterColumns)> {
+        println!("==== Import Roster ====");
+        let filename = input::get_optional_line("Roster CSV file (blank to cancel): ");
+        if filename.is_empty() {
+            Self::clear_screen();
+            return None;
+        }
+
+        println!("\nColumn headers in the roster (blank for the default)");
+        let column = |prompt: &str, default: String| match input::get_optional_line(&format!(
+            "{} [{}]: ",
+            prompt, default
+        )) {
+            header if header.is_empty() => default,
+            header => header,
+        };
+        let defaults = RosterColumns::default();
+        let columns = RosterColumns {
+            name: column("Name", defaults.name),
+            number: column("Student Number", defaults.number),
+            email: column("Email", defaults.email),
+            section: column("Section", defaults.section),
+        };
+        Self::clear_screen();
+        Some((filename, columns))
+    }
+
     fn create_assignment() -> Assignment {
         println!("==== New Assignment ====");
         let name = input::get_line("Assignment Name: ");
@@ -70,7 +149,7 @@ impl View for AppView {
                     &format!("Marks for {}.{}: ", i, part_num),
                     "Input must be a positive number",
                 ) {
-                    x if x <= 0 => {
+                    0 => {
                         if part_num > 1 {
                             break;
                         } else {
@@ -88,50 +167,131 @@ impl View for AppView {
         asn
     }
 
-    fn load_assignment() -> Option<Assignment> {
-        let mut files: Vec<String> = fs::read_dir("./")
-            .unwrap()
-            .map(|p| {
-                p.unwrap()
-                    .path()
-                    .display()
-                    .to_string()
-                    .strip_prefix("./")
-                    .unwrap()
-                    .to_string()
+    fn choose_storage_format() -> StorageFormat {
+        let header = "Save Format";
+        let menu = vec![
+            "Easy Mark (.emark)".to_string(),
+            "JSON (.emark.json)".to_string(),
+        ];
+        match Self::show_menu(header, &menu) {
+            2 => StorageFormat::Json,
+            _ => StorageFormat::Pickle,
+        }
+    }
+
+    fn choose_lms() -> Option<Lms> {
+        let header = "Export Grades For";
+        let systems = Lms::all();
+        let mut menu: Vec<String> = systems
+            .iter()
+            .map(|l| {
+                let name = l.name();
+                name[..1].to_uppercase() + &name[1..]
             })
-            .filter(|s| s.ends_with(".emark"))
             .collect();
+        menu.push("Cancel".to_string());
 
-        if files.len() > 0 {
-            files.push("Exit".to_string());
-            let header = "Load Assignment";
-            let choice = (Self::show_menu(&header, &files) - 1) as usize;
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
+        systems.get(choice).copied()
+    }
 
-            if choice >= files.len() - 1 {
-                None
-            } else {
-                let asn = AppFileOps::load_assignment(&files[choice]);
-                Some(asn)
-            }
+    fn choose_usage_order() -> Option<UsageOrder> {
+        let header = "Order Comments By";
+        let orders = UsageOrder::all();
+        let mut menu: Vec<String> = orders.iter().map(|o| o.describe().to_string()).collect();
+        menu.push("Cancel".to_string());
+
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
+        orders.get(choice).copied()
+    }
+
+    fn choose_renderer() -> Option<Box<dyn GradeSheetRenderer>> {
+        let header = "Export Grade Sheets As";
+        let mut renderers = render::built_in_renderers();
+        let mut menu: Vec<String> = renderers.iter().map(|r| r.describe().to_string()).collect();
+        menu.push("Cancel".to_string());
+
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
+        if choice < renderers.len() {
+            Some(renderers.swap_remove(choice))
         } else {
+            None
+        }
+    }
+
+    fn choose_assignment_file() -> Option<LoadChoice> {
+        let entries = match fs::read_dir("./") {
+            Ok(entries) => entries,
+            Err(e) => {
+                Self::show_error(&FileError::reading("./", e));
+                return None;
+            }
+        };
+        let names: Vec<String> = entries
+            .filter_map(|p| p.ok())
+            .map(|p| p.file_name().to_string_lossy().to_string())
+            .sorted()
+            .collect();
+        let mut files: Vec<String> = names
+            .iter()
+            .filter(|s| storage::is_assignment_file(s))
+            .cloned()
+            .collect();
+        let backups: Vec<String> = names
+            .into_iter()
+            .filter(|s| backup::parse_backup_name(s).is_some())
+            .collect();
+
+        if files.is_empty() && backups.is_empty() {
             println!("*** No easy-mark files ***\n");
+            return None;
+        }
+
+        let num_files = files.len();
+        files.push("Restore From Backup".to_string());
+        files.push("Exit".to_string());
+        let header = "Load Assignment";
+        let choice = (Self::show_menu(header, &files) - 1) as usize;
+
+        if choice < num_files {
+            Some(LoadChoice::File(files.swap_remove(choice)))
+        } else if choice == num_files {
+            Self::choose_backup(backups).map(LoadChoice::Backup)
+        } else {
+            None
+        }
+    }
+
+    fn choose_backup(mut backups: Vec<String>) -> Option<String> {
+        if backups.is_empty() {
+            println!("*** No backups ***\n");
+            return None;
+        }
+
+        // Newest first, since that is usually the one wanted.
+        backups.reverse();
+        let mut menu: Vec<String> = backups
+            .iter()
+            .filter_map(|b| backup::parse_backup_name(b))
+            .map(|(file, time)| format!("{} ({})", file, time.format("%Y-%m-%d %H:%M:%S")))
+            .collect();
+        menu.push("Cancel".to_string());
+
+        let header = "Restore From Backup";
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
+        if choice < backups.len() {
+            Some(backups.swap_remove(choice))
+        } else {
             None
         }
     }
 
     fn new_comment() -> Option<(f32, String)> {
         println!("==== Add New Comment ====");
-        let deduction: f32 = loop {
-            match input::get_f32("Deduction: ", "Must be a whole or decimal number") {
-                x if x < 0.0 => println!(
-                    "\n*** {}. {}. ***\n",
-                    "Deductions must be 0 or greater",
-                    "They will be negative when calculating marks"
-                ),
-                x => break x,
-            }
-        };
+        let deduction: f32 = input::get_f32(
+            "Deduction (negative for a bonus): ",
+            "Must be a whole or decimal number",
+        );
         let text: String = input::get_line("Comment: ");
         let satisfied: String = input::get_line("Satisfied? (y/n): ");
         Self::clear_screen();
@@ -144,13 +304,13 @@ impl View for AppView {
 
     fn add_existing_comment(
         assignment: &Assignment,
-        student: &str,
+        student: u64,
         question: &Question,
     ) -> Option<u64> {
         let header = "Add Existing Comment";
         let comments = assignment.unused_comments_for(student, question);
 
-        if comments.len() == 0 {
+        if comments.is_empty() {
             Self::clear_screen();
             println!("*** No available comments ***\n");
             return None;
@@ -158,11 +318,11 @@ impl View for AppView {
 
         let mut menu: Vec<String> = comments
             .iter()
-            .map(|c| format!("[-{}]\n   {} ", c.deduction, c.text))
+            .map(|c| format!("[{}]\n   {} ", deduction_label(c.deduction), c.text))
             .collect();
         menu.push("Cancel".to_string());
 
-        let choice = (Self::show_menu(&header, &menu) - 1) as usize;
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
         Self::clear_screen();
 
         if choice < comments.len() {
@@ -174,13 +334,13 @@ impl View for AppView {
 
     fn edit_comment(
         assignment: &Assignment,
-        student: &str,
+        student: u64,
         question: &Question,
     ) -> Option<(f32, String, u64)> {
         let header = "Edit Comment *** For ALL Users ***";
         let comments = assignment.students_comments_for(student, question);
 
-        if comments.len() == 0 {
+        if comments.is_empty() {
             Self::clear_screen();
             println!("*** No comments have been added ***\n");
             return None;
@@ -188,27 +348,23 @@ impl View for AppView {
 
         let mut menu: Vec<String> = comments
             .iter()
-            .map(|c| format!("[-{}]\n   {} ", c.deduction, c.text))
+            .map(|c| format!("[{}]\n   {} ", deduction_label(c.deduction), c.text))
             .collect();
         menu.push("Cancel".to_string());
 
-        let choice = (Self::show_menu(&header, &menu) - 1) as usize;
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
         Self::clear_screen();
 
         if choice < comments.len() {
             println!("==== Edit Comment ====");
             let deduction: f32 = loop {
                 let num: String = input::readline_with_initial(
-                    "Deduction: ",
+                    "Deduction (negative for a bonus): ",
                     (&comments[choice].deduction.to_string(), ""),
                 );
                 match num.parse::<f32>() {
                     Ok(x) => break x,
-                    _ => println!(
-                        "\n*** {}. {}. ***\n",
-                        "Deductions must be 0 or greater",
-                        "They will be negative when calculating marks"
-                    ),
+                    _ => println!("\n*** Must be a whole or decimal number ***\n"),
                 }
             };
 
@@ -217,7 +373,7 @@ impl View for AppView {
             let satisfied: String = input::get_line("Satisfied? (y/n): ");
             Self::clear_screen();
 
-            match satisfied.to_lowercase() == "y".to_string() {
+            match satisfied.to_lowercase() == "y" {
                 true => Some((deduction, text, comments[choice].id)),
                 false => None,
             }
@@ -226,11 +382,11 @@ impl View for AppView {
         }
     }
 
-    fn remove_comment(assignment: &Assignment, student: &str, question: &Question) -> Option<u64> {
+    fn remove_comment(assignment: &Assignment, student: u64, question: &Question) -> Option<u64> {
         let header = "Remove Comment".to_string();
         let comments = assignment.students_comments_for(student, question);
 
-        if comments.len() == 0 {
+        if comments.is_empty() {
             Self::clear_screen();
             println!("*** No comments have been added ***\n");
             return None;
@@ -238,7 +394,7 @@ impl View for AppView {
 
         let mut menu: Vec<String> = comments
             .iter()
-            .map(|c| format!("[-{}]\n   {} ", c.deduction, c.text))
+            .map(|c| format!("[{}]\n   {} ", deduction_label(c.deduction), c.text))
             .collect();
         menu.push("Cancel".to_string());
 
@@ -252,22 +408,235 @@ impl View for AppView {
         }
     }
 
-    fn show_grade_sheet(assignment: &Assignment, student: &str) {
+    fn show_structure(assignment: &Assignment) {
+        println!("==== Questions (Out of {}) ====", assignment.out_of());
+        for q in assignment.get_questions().iter() {
+            println!("{}.{} -- {} marks", q.num, q.part, q.out_of);
+        }
+        if let Some(due) = assignment.due_date() {
+            println!(
+                "Due {} -- {}",
+                due.format(late::DATE_TIME_FORMAT),
+                assignment.late_policy().describe()
+            );
+        }
+        println!();
+    }
+
+    fn new_question_part(assignment: &Assignment) -> Option<Question> {
+        println!("==== Add Question Part ====");
+        let num = input::get_u32("Question Number: ", "Input must be a positive number");
+        let part = input::get_u32("Part: ", "Input must be a positive number");
+        let out_of = input::get_u32("Marks: ", "Input must be a positive number");
+        Self::clear_screen();
+
+        let question = Question::new(num, part, out_of);
+        if assignment.question_exists(&question) {
+            println!("*** Question {}.{} already exists ***\n", num, part);
+            None
+        } else {
+            Some(question)
+        }
+    }
+
+    fn choose_question(assignment: &Assignment, header: &str) -> Option<Question> {
+        let questions = assignment.get_questions();
+        let mut menu: Vec<String> = questions
+            .iter()
+            .map(|q| format!("{}.{} ({} marks)", q.num, q.part, q.out_of))
+            .collect();
+        menu.push("Cancel".to_string());
+
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
+        questions.get(choice).cloned()
+    }
+
+    fn choose_comment(assignment: &Assignment, question: &Question, header: &str) -> Option<u64> {
+        let comments = assignment.comments_for(question);
+        if comments.is_empty() {
+            println!(
+                "*** {}.{} has no comments ***\n",
+                question.num, question.part
+            );
+            return None;
+        }
+
+        let mut menu: Vec<String> = comments
+            .iter()
+            .map(|c| {
+                let raw = if c.raw_latex { " (raw LaTeX)" } else { "" };
+                format!("[{}]{}\n   {} ", deduction_label(c.deduction), raw, c.text)
+            })
+            .collect();
+        menu.push("Cancel".to_string());
+
+        let choice = (Self::show_menu(header, &menu) - 1) as usize;
+        Self::clear_screen();
+        comments.get(choice).map(|c| c.id)
+    }
+
+    fn new_out_of(question: &Question) -> Option<u32> {
+        println!(
+            "==== Change Marks For {}.{} ====",
+            question.num, question.part
+        );
+        let out_of: u32 = loop {
+            let num: String =
+                input::readline_with_initial("Marks: ", (&question.out_of.to_string(), ""));
+            match num.parse::<u32>() {
+                Ok(x) => break x,
+                _ => println!("\n*** Input must be a positive number ***\n"),
+            }
+        };
+        Self::clear_screen();
+        Some(out_of)
+    }
+
+    fn renumber_question(assignment: &Assignment, question: &Question) -> Option<(u32, u32)> {
+        println!("==== Renumber {}.{} ====", question.num, question.part);
+        let num = input::get_u32("Question Number: ", "Input must be a positive number");
+        let part = input::get_u32("Part: ", "Input must be a positive number");
+        Self::clear_screen();
+
+        let renumbered = Question::new(num, part, question.out_of);
+        if !renumbered.same_part(question) && assignment.question_exists(&renumbered) {
+            println!("*** Question {}.{} already exists ***\n", num, part);
+            None
+        } else {
+            Some((num, part))
+        }
+    }
+
+    fn new_scaling(assignment: &Assignment) -> Option<Scaling> {
+        let menu = vec![
+            "Scale To A Target Mean".to_string(),
+            "Add A Constant".to_string(),
+            "Cap At A Maximum".to_string(),
+            "Square Root Curve".to_string(),
+            "Map Onto A Different Out Of".to_string(),
+            "Cancel".to_string(),
+        ];
+        let choice = Self::show_menu("Add Scaling Step", &menu);
+        let number = "Input must be a number";
+        let scaling = match choice {
+            1 => Scaling::ToMean(input::get_f32("Target Mean: ", number)),
+            2 => Scaling::Add(input::get_f32("Marks To Add: ", number)),
+            3 => Scaling::Cap(input::get_f32("Maximum: ", number)),
+            4 => Scaling::SquareRoot,
+            5 => Scaling::OutOf(input::get_u32(
+                &format!("New Out Of (currently {}): ", assignment.scaled_out_of()),
+                "Input must be a positive number",
+            )),
+            _ => return None,
+        };
+        Self::clear_screen();
+        Some(scaling)
+    }
+
+    fn choose_date_time(header: &str, current: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+        println!("==== {} ====", header);
+        println!("Enter YYYY-MM-DD HH:MM, or leave blank for none\n");
+        let current = current.map_or(String::new(), |d| {
+            d.format(late::DATE_TIME_FORMAT).to_string()
+        });
+        let date_time = loop {
+            let text = input::readline_with_initial("Date: ", (&current, ""));
+            if text.trim().is_empty() {
+                break None;
+            }
+            match late::parse_date_time(&text) {
+                Some(d) => break Some(d),
+                None => println!("\n*** Input must be a date like 2026-10-16 23:59 ***\n"),
+            }
+        };
+        Self::clear_screen();
+        date_time
+    }
+
+    fn new_late_policy(current: LatePolicy) -> Option<LatePolicy> {
+        let header = format!("Late Policy ({})", current.describe());
+        let menu = vec![
+            "No Penalty".to_string(),
+            "Percent Per Day".to_string(),
+            "Fixed Marks".to_string(),
+            "Cancel".to_string(),
+        ];
+        let number = "Input must be a number";
+        let penalty = match Self::show_menu(&header, &menu) {
+            1 => LatePenalty::None,
+            2 => LatePenalty::PercentPerDay(input::get_f32("Percent Per Day: ", number)),
+            3 => LatePenalty::Fixed(input::get_f32("Marks: ", number)),
+            _ => return None,
+        };
+        let cutoff_days = loop {
+            let days = input::get_optional_line(
+                "Days late before getting zero (0 for any, blank for never): ",
+            );
+            if days.trim().is_empty() {
+                break None;
+            }
+            match days.trim().parse::<u32>() {
+                Ok(d) => break Some(d),
+                _ => println!("\n*** Input must be a positive number ***\n"),
+            }
+        };
+        Self::clear_screen();
+        Some(LatePolicy {
+            penalty,
+            cutoff_days,
+        })
+    }
+
+    fn choose_template(current: &str) -> Option<String> {
+        println!("==== LaTeX Template ====");
+        println!("Enter the path of a template file, or leave blank for the default\n");
+        let path = input::readline_with_initial("Template: ", (current, ""));
+        Self::clear_screen();
+        if path.trim().is_empty() {
+            None
+        } else {
+            Some(path.trim().to_string())
+        }
+    }
+
+    fn confirm(message: &str) -> bool {
+        let answer: String = input::get_line(&format!("{} (y/n): ", message));
+        Self::clear_screen();
+        answer.to_lowercase() == "y"
+    }
+
+    fn show_grade_sheet(assignment: &Assignment, student: u64) {
         println!("================================================================");
         println!("{} - {}", assignment.course, assignment.title);
-        println!("{}", student);
+        println!("{}", assignment.get_student(student).label());
         println!(
-            "Total: {}/{}\n",
+            "Total: {}/{}",
             assignment.students_total(student),
             assignment.out_of()
         );
+        let penalty = assignment.students_late_penalty(student);
+        if penalty > 0.0 {
+            println!(
+                "Late Penalty: {} ({} days late)",
+                deduction_label(penalty),
+                assignment.students_days_late(student)
+            );
+        }
+        if !assignment.scaling().is_empty() {
+            println!(
+                "Scaled: {}/{}",
+                assignment.students_scaled_total(student),
+                assignment.scaled_out_of()
+            );
+        }
+        println!();
         for q in assignment.get_questions().iter() {
-            Self::show_question_info(assignment, student, &q);
+            Self::show_question_info(assignment, student, q);
         }
         println!("================================================================\n\n");
     }
 
-    fn show_question_info(assignment: &Assignment, student: &str, question: &Question) {
+    fn show_question_info(assignment: &Assignment, student: u64, question: &Question) {
         let comments = assignment.students_comments_for(student, question);
         let mark = assignment.students_mark_for(student, question);
 
@@ -275,13 +644,79 @@ impl View for AppView {
         println!("Question {}.{}", question.num, question.part);
         println!("Grade {}/{}\n", mark, question.out_of);
 
-        if comments.len() == 0 {
+        if !comments.is_empty() {
+            for com in comments {
+                println!("[{}]\n   {}", deduction_label(com.deduction), com.text);
+            }
+        } else if assignment.is_graded(student, question) {
             println!("** Well Done **");
         } else {
-            for com in comments {
-                println!("[-{}]\n   {}", com.deduction, com.text);
+            println!("** Not Graded Yet **");
+        }
+        println!();
+    }
+
+    fn show_comment_bank(assignment: &Assignment, student: u64, question: &Question) {
+        Self::show_question_info(assignment, student, question);
+        let unused = assignment.unused_comments_for(student, question);
+
+        println!("Other comments for {}\n", question.label());
+        if unused.is_empty() {
+            println!("** None yet **");
+        } else {
+            for com in unused {
+                println!("[{}]\n   {}", deduction_label(com.deduction), com.text);
             }
         }
-        println!("");
+        println!();
+    }
+
+    fn show_statistics(assignment: &Assignment) {
+        println!("================================================================");
+        println!("{}", stats::convert_text(assignment));
+        println!("================================================================\n\n");
+    }
+
+    fn show_scaling_preview(assignment: &Assignment, steps: &[Scaling]) {
+        println!("================================================================");
+        println!("{}", scaling::convert_preview(assignment, steps));
+        println!("================================================================\n\n");
+    }
+
+    fn show_comment_usage(assignment: &Assignment, order: UsageOrder) {
+        println!("================================================================");
+        println!("{}", usage::convert_text(assignment, order));
+        println!("================================================================\n\n");
+    }
+}
+
+// Searches the labels by fuzzy matching and returns the index of the chosen
+// one. A single match, or an exact match, is chosen straight away, otherwise
+// the best matches are shown in a menu.
+fn choose_match(header: &str, labels: &[String]) -> Option<usize> {
+    println!("==== {} ====", header);
+    println!("Type to search, Tab to list matches, or leave blank to cancel\n");
+    let query = input::get_with_fuzzy_completion("Search: ", labels.to_vec());
+    AppView::clear_screen();
+    if query.is_empty() {
+        return None;
+    }
+
+    let matches = search::rank(&query, labels);
+    match matches.first() {
+        None => {
+            AppView::show_message(&format!("Nothing matches '{}'", query));
+            None
+        }
+        Some(&best) if matches.len() == 1 || labels[best].eq_ignore_ascii_case(&query) => {
+            Some(best)
+        }
+        Some(_) => {
+            let shown: Vec<usize> = matches.into_iter().take(MAX_MATCHES).collect();
+            let mut menu: Vec<String> = shown.iter().map(|&i| labels[i].clone()).collect();
+            menu.push("Cancel".to_string());
+            let choice = (AppView::show_menu(header, &menu) - 1) as usize;
+            shown.get(choice).copied()
+        }
     }
 }
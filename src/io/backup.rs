@@ -0,0 +1,173 @@
+use crate::io::FileError;
+use chrono::{Duration, Local, NaiveDateTime};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// The number of backups kept for each assignment file.
+pub const MAX_BACKUPS: usize = 5;
+
+/// The minimum number of minutes between backups of a file. Assignments are
+/// saved after every change, so without this the backups would all be from
+/// the last few minutes of grading.
+pub const BACKUP_INTERVAL_MINS: i64 = 10;
+
+// The format of the timestamp in backup file names. It sorts in time order.
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const BACKUP_EXT: &str = ".bak";
+
+/// The name of the backup of a file taken at the given time.
+pub fn backup_name(filename: &str, time: &NaiveDateTime) -> String {
+    format!("{}.{}{}", filename, time.format(STAMP_FORMAT), BACKUP_EXT)
+}
+
+/// Splits a backup file name into the name of the file it is a backup of and
+/// the time it was taken. Returns None if the name is not a backup.
+pub fn parse_backup_name(backup: &str) -> Option<(String, NaiveDateTime)> {
+    let rest = backup.strip_suffix(BACKUP_EXT)?;
+    let dot = rest.rfind('.')?;
+    let time = NaiveDateTime::parse_from_str(&rest[dot + 1..], STAMP_FORMAT).ok()?;
+    Some((rest[..dot].to_string(), time))
+}
+
+/// Lists the backups of a file, oldest first.
+pub fn list_backups(filename: &str) -> Result<Vec<String>, FileError> {
+    let dir = match Path::new(filename).parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => Path::new(".").to_path_buf(),
+    };
+    let entries = fs::read_dir(&dir).map_err(|e| FileError::reading(filename, e))?;
+
+    let mut backups: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| dir.join(e.file_name()).to_string_lossy().to_string())
+        .map(|p| p.strip_prefix("./").map(|s| s.to_string()).unwrap_or(p))
+        .filter(|b| matches!(parse_backup_name(b), Some((f, _)) if f == filename))
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+/// Backs up a file if it exists and the newest backup of it is older than
+/// the backup interval, then removes the oldest backups so that at most
+/// MAX_BACKUPS are kept.
+pub fn rotate(filename: &str) -> Result<(), FileError> {
+    if !Path::new(filename).exists() {
+        return Ok(());
+    }
+
+    let now = Local::now().naive_local();
+    let backups = list_backups(filename)?;
+    let due = match backups.last().and_then(|b| parse_backup_name(b)) {
+        Some((_, last)) => now - last >= Duration::minutes(BACKUP_INTERVAL_MINS),
+        None => true,
+    };
+    if due {
+        take_backup(filename, &now)?;
+    }
+    prune(filename)
+}
+
+/// Backs up a file now, regardless of when it was last backed up.
+pub fn take_backup(filename: &str, time: &NaiveDateTime) -> Result<(), FileError> {
+    let backup = backup_name(filename, time);
+    fs::copy(filename, &backup).map_err(|e| FileError::writing(&backup, e))?;
+    Ok(())
+}
+
+// Removes the oldest backups of a file beyond MAX_BACKUPS.
+fn prune(filename: &str) -> Result<(), FileError> {
+    let backups = list_backups(filename)?;
+    let extra = backups.len().saturating_sub(MAX_BACKUPS);
+    for old in backups.iter().take(extra) {
+        fs::remove_file(old).map_err(|e| FileError::writing(old, e))?;
+    }
+    Ok(())
+}
+
+/// Writes the bytes to a temporary file next to the given one and then
+/// renames it over the original, so the original is never left half written.
+pub fn write_atomic(filename: &str, bytes: &[u8]) -> Result<(), FileError> {
+    let tmp = format!("{}.tmp", filename);
+    let write = || -> std::io::Result<()> {
+        let mut f = fs::File::create(&tmp)?;
+        f.write_all(bytes)?;
+        f.sync_all()
+    };
+    if let Err(e) = write() {
+        let _ = fs::remove_file(&tmp);
+        return Err(FileError::writing(filename, e));
+    }
+    fs::rename(&tmp, filename).map_err(|e| FileError::writing(filename, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn time(h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2021, 3, 14)
+            .unwrap()
+            .and_hms_opt(h, m, 0)
+            .unwrap()
+    }
+
+    // A fresh directory under the system temp dir for a filesystem test.
+    fn test_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("easy_mark_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn it_names_backups_after_the_file_and_time() {
+        let name = backup_name("CS_1000_A5.emark", &time(9, 5));
+        assert_eq!(name, "CS_1000_A5.emark.20210314-090500.bak");
+        assert_eq!(
+            parse_backup_name(&name),
+            Some(("CS_1000_A5.emark".to_string(), time(9, 5)))
+        );
+    }
+
+    #[test]
+    fn it_does_not_parse_files_that_are_not_backups() {
+        assert_eq!(parse_backup_name("CS_1000_A5.emark"), None);
+        assert_eq!(parse_backup_name("CS_1000_A5.emark.bak"), None);
+        assert_eq!(parse_backup_name("notes.yesterday.bak"), None);
+    }
+
+    #[test]
+    fn it_keeps_only_the_newest_backups() {
+        let dir = test_dir("prune");
+        let file = format!("{}/a.emark", dir);
+        fs::write(&file, b"data").unwrap();
+        for m in 0..(MAX_BACKUPS as u32 + 2) {
+            take_backup(&file, &time(10, m)).unwrap();
+        }
+        // A backup of a different file should be left alone.
+        let other = format!("{}/b.emark", dir);
+        fs::write(&other, b"data").unwrap();
+        take_backup(&other, &time(8, 0)).unwrap();
+
+        prune(&file).unwrap();
+        let backups = list_backups(&file).unwrap();
+        assert_eq!(backups.len(), MAX_BACKUPS);
+        assert_eq!(backups[0], backup_name(&file, &time(10, 2)));
+        assert_eq!(list_backups(&other).unwrap().len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn it_replaces_files_atomically() {
+        let dir = test_dir("atomic");
+        let file = format!("{}/a.emark", dir);
+        write_atomic(&file, b"first").unwrap();
+        write_atomic(&file, b"second").unwrap();
+
+        assert_eq!(fs::read(&file).unwrap(), b"second");
+        assert!(!Path::new(&format!("{}.tmp", file)).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
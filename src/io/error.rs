@@ -0,0 +1,117 @@
+use std::fmt;
+use std::io;
+
+/// The ways that reading and writing easy-mark files can fail.
+#[derive(Debug, PartialEq)]
+pub enum FileError {
+    /// The file does not exist.
+    NotFound(String),
+    /// The file could not be accessed with the current permissions.
+    PermissionDenied(String),
+    /// The file could not be read. Holds the path and the reason.
+    ReadFailed(String, String),
+    /// The file could be read, but is not an easy-mark assignment. Holds the
+    /// path and the reason.
+    Corrupt(String, String),
+    /// The file could not be written. Holds the path and the reason.
+    WriteFailed(String, String),
+    /// A template could not be parsed or filled in. Holds the path and the
+    /// reason.
+    InvalidTemplate(String, String),
+    /// Grade sheets could not be finished after they were written, such as
+    /// by compiling them to PDF. Holds the path and a summary of the errors.
+    CompileFailed(String, String),
+}
+
+impl FileError {
+    /// Classifies an io error that happened while reading the given path.
+    pub fn reading(path: &str, err: io::Error) -> FileError {
+        match err.kind() {
+            io::ErrorKind::NotFound => FileError::NotFound(path.to_string()),
+            io::ErrorKind::PermissionDenied => FileError::PermissionDenied(path.to_string()),
+            _ => FileError::ReadFailed(path.to_string(), err.to_string()),
+        }
+    }
+
+    /// Classifies an io error that happened while writing the given path.
+    pub fn writing(path: &str, err: io::Error) -> FileError {
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => FileError::PermissionDenied(path.to_string()),
+            _ => FileError::WriteFailed(path.to_string(), err.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileError::NotFound(path) => write!(f, "Could not find {}", path),
+            FileError::PermissionDenied(path) => write!(f, "Permission denied for {}", path),
+            FileError::ReadFailed(path, reason) => {
+                write!(f, "Could not read {}: {}", path, reason)
+            }
+            FileError::Corrupt(path, reason) => {
+                write!(f, "{} is not a valid easy-mark file: {}", path, reason)
+            }
+            FileError::WriteFailed(path, reason) => {
+                write!(f, "Could not write {}: {}", path, reason)
+            }
+            FileError::InvalidTemplate(path, reason) => {
+                write!(f, "{} is not a valid template: {}", path, reason)
+            }
+            FileError::CompileFailed(path, errors) => {
+                write!(f, "Could not compile {}:\n{}", path, errors)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_classifies_read_errors() {
+        let not_found = io::Error::new(io::ErrorKind::NotFound, "gone");
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "no");
+        let other = io::Error::new(io::ErrorKind::InvalidData, "bad bytes");
+
+        assert_eq!(
+            FileError::reading("a.emark", not_found),
+            FileError::NotFound("a.emark".to_string())
+        );
+        assert_eq!(
+            FileError::reading("a.emark", denied),
+            FileError::PermissionDenied("a.emark".to_string())
+        );
+        assert_eq!(
+            FileError::reading("roster.csv", other),
+            FileError::ReadFailed("roster.csv".to_string(), "bad bytes".to_string())
+        );
+        assert_eq!(
+            FileError::reading(
+                "roster.csv",
+                io::Error::new(io::ErrorKind::InvalidData, "x")
+            )
+            .to_string(),
+            "Could not read roster.csv: x"
+        );
+    }
+
+    #[test]
+    fn it_classifies_write_errors() {
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "no");
+        let full = io::Error::new(io::ErrorKind::WriteZero, "disk full");
+
+        assert_eq!(
+            FileError::writing("a.tex", denied),
+            FileError::PermissionDenied("a.tex".to_string())
+        );
+        assert_eq!(
+            FileError::writing("a.tex", full),
+            FileError::WriteFailed("a.tex".to_string(), "disk full".to_string())
+        );
+    }
+}
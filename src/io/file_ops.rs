@@ -1,12 +1,31 @@
+use crate::data::lms::Lms;
+use crate::data::template::Template;
+use crate::data::usage::UsageOrder;
 use crate::data::Assignment;
+use crate::io::render::GradeSheetRenderer;
+use crate::io::FileError;
 
 #[cfg(test)]
 use mockall::automock;
 
 #[cfg_attr(test, automock)]
 pub trait FileOps {
-    fn load_assignment(filename: &str) -> Assignment;
-    fn save_assignment(assignment: &Assignment);
-    fn save_latex_grade_sheet(assignment: &Assignment, student: &String);
-    fn save_all_latex_grade_sheets(assignment: &Assignment);
+    fn load_assignment(filename: &str) -> Result<Assignment, FileError>;
+    fn save_assignment(assignment: &Assignment, filename: &str) -> Result<(), FileError>;
+    fn load_roster(filename: &str) -> Result<String, FileError>;
+    fn load_template(filename: &str) -> Result<Template, FileError>;
+    fn restore_backup(backup_file: &str) -> Result<String, FileError>;
+    fn save_grade_sheet(
+        assignment: &Assignment,
+        student: u64,
+        renderer: &dyn GradeSheetRenderer,
+    ) -> Result<(), FileError>;
+    fn save_all_grade_sheets(
+        assignment: &Assignment,
+        renderer: &dyn GradeSheetRenderer,
+    ) -> Result<(), FileError>;
+    fn save_gradebook(assignment: &Assignment, long: bool) -> Result<(), FileError>;
+    fn save_lms_grades(assignment: &Assignment, lms: Lms) -> Result<(), FileError>;
+    fn save_statistics(assignment: &Assignment, latex: bool) -> Result<(), FileError>;
+    fn save_comment_usage(assignment: &Assignment, order: UsageOrder) -> Result<(), FileError>;
 }
@@ -1,9 +1,29 @@
-use rustyline::Editor;
+use crate::data::search;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::{Hint, Hinter};
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
 
 pub fn get_line(prompt: &str) -> String {
     readline_with_initial(prompt, ("", ""))
 }
 
+/// Like get_line, but an empty answer is allowed for optional details.
+pub fn get_optional_line(prompt: &str) -> String {
+    let mut rl = Editor::<()>::new();
+    rl.readline(prompt).unwrap().trim().to_string()
+}
+
+/// Reads a line while hinting at the candidate that best fuzzy matches what
+/// has been typed so far. Tab completes to the matching candidates, best
+/// first. An empty answer is allowed for cancelling.
+pub fn get_with_fuzzy_completion(prompt: &str, candidates: Vec<String>) -> String {
+    let mut rl = Editor::<FuzzyHelper>::new();
+    rl.set_helper(Some(FuzzyHelper { candidates }));
+    rl.readline(prompt).unwrap().trim().to_string()
+}
+
 pub fn readline_with_initial(prompt: &str, initial: (&str, &str)) -> String {
     loop {
         let mut rl = Editor::<()>::new();
@@ -38,3 +58,60 @@ pub fn get_f32(prompt: &str, error_msg: &str) -> f32 {
         }
     }
 }
+
+// Completes and hints whole lines from candidates that fuzzy match them.
+struct FuzzyHelper {
+    candidates: Vec<String>,
+}
+
+impl FuzzyHelper {
+    fn matches(&self, line: &str) -> Vec<String> {
+        search::rank(line, &self.candidates)
+            .into_iter()
+            .map(|i| self.candidates[i].clone())
+            .collect()
+    }
+}
+
+impl Completer for FuzzyHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        _pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok((0, self.matches(line)))
+    }
+}
+
+impl Hinter for FuzzyHelper {
+    type Hint = FuzzyHint;
+
+    fn hint(&self, line: &str, _pos: usize, _ctx: &Context<'_>) -> Option<FuzzyHint> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        let best = self.matches(line).into_iter().next()?;
+        Some(FuzzyHint(format!("  -> {}", best)))
+    }
+}
+
+impl Highlighter for FuzzyHelper {}
+impl Validator for FuzzyHelper {}
+impl Helper for FuzzyHelper {}
+
+// A hint that is only shown, since the best match is not a continuation of
+// what has been typed.
+struct FuzzyHint(String);
+
+impl Hint for FuzzyHint {
+    fn display(&self) -> &str {
+        &self.0
+    }
+
+    fn completion(&self) -> Option<&str> {
+        None
+    }
+}
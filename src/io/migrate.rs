@@ -0,0 +1,326 @@
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// The version of the saved file layout written by this build.
+/// Bump it and add a step to `upgrade` whenever the saved layout of an
+/// `Assignment`, or anything it holds, changes in a way that serde can not
+/// fill in with defaults. Older files are upgraded one version at a time.
+pub const CURRENT_VERSION: u64 = 3;
+
+/// Upgrades the contents of a saved file to the current layout and returns
+/// the assignment data ready to be deserialized.
+/// Returns an error if the file was saved by a newer version of easy-mark or
+/// is missing its assignment data.
+pub fn upgrade(saved: Value) -> Result<Value, String> {
+    let (mut version, mut asn) = split_version(saved)?;
+    if version > CURRENT_VERSION {
+        return Err(format!(
+            "file version {} is newer than the supported version {}",
+            version, CURRENT_VERSION
+        ));
+    }
+
+    while version < CURRENT_VERSION {
+        asn = match version {
+            0 => v0_to_v1(asn),
+            1 => v1_to_v2(asn)?,
+            2 => v2_to_v3(asn),
+            _ => unreachable!(),
+        };
+        version += 1;
+    }
+    Ok(asn)
+}
+
+// Splits a saved file into its version and assignment data. Files saved
+// before versioning was added hold a bare assignment and are version 0.
+fn split_version(saved: Value) -> Result<(u64, Value), String> {
+    match saved {
+        Value::Object(mut map) if map.contains_key("version") => {
+            let version = map["version"].as_u64().ok_or("invalid file version")?;
+            let asn = map.remove("assignment").ok_or("missing assignment data")?;
+            Ok((version, asn))
+        }
+        asn => Ok((0, asn)),
+    }
+}
+
+// Version 1 wrapped the assignment with its version number, but did not
+// change the assignment layout itself.
+fn v0_to_v1(asn: Value) -> Value {
+    asn
+}
+
+// Version 2 made students records with an id, and comments and history refer
+// to students by id instead of by name.
+fn v1_to_v2(mut asn: Value) -> Result<Value, String> {
+    let names: Vec<String> = match asn.get("students") {
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|n| n.as_str().map(String::from).ok_or("invalid student name"))
+            .collect::<Result<_, _>>()?,
+        _ => return Err("missing students".to_string()),
+    };
+    let mut ids = HashMap::new();
+    for (id, name) in names.iter().enumerate() {
+        ids.entry(name.clone()).or_insert(id as u64);
+    }
+    let id_of = |name: &Value| -> Result<Value, String> {
+        name.as_str()
+            .and_then(|n| ids.get(n))
+            .map(|id| json!(id))
+            .ok_or_else(|| format!("comment for unknown student {}", name))
+    };
+
+    let map = asn.as_object_mut().ok_or("invalid assignment data")?;
+    let students: Vec<Value> = names
+        .iter()
+        .enumerate()
+        .map(|(id, name)| json!({"id": id, "name": name}))
+        .collect();
+    map.insert("students".to_string(), Value::Array(students));
+    map.insert("next_student_id".to_string(), json!(names.len()));
+
+    if let Some(Value::Array(questions)) = map.get_mut("questions") {
+        for q in questions.iter_mut() {
+            if let Some(Value::Array(comments)) = q.get_mut("comments") {
+                for c in comments.iter_mut() {
+                    comment_names_to_ids(c, &id_of)?;
+                }
+            }
+        }
+    }
+
+    if let Some(Value::Object(history)) = map.get_mut("history") {
+        for stack in ["undo", "redo"].iter() {
+            if let Some(Value::Array(actions)) = history.get_mut(*stack) {
+                for action in actions.iter_mut().filter_map(|a| a.as_object_mut()) {
+                    if let Some(name) = action.get("student") {
+                        let id = id_of(name)?;
+                        action.insert("student".to_string(), id);
+                    }
+                    if let Some(c) = action.get_mut("comment") {
+                        comment_names_to_ids(c, &id_of)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(asn)
+}
+
+// Version 3 tracks which students have been graded on each question. Before
+// that, the only sign of grading was a comment, so students with a comment
+// on a question are taken to have been graded on it.
+fn v2_to_v3(mut asn: Value) -> Value {
+    if let Some(Value::Array(questions)) = asn.get_mut("questions") {
+        for q in questions.iter_mut().filter_map(|q| q.as_object_mut()) {
+            let mut graded: Vec<u64> = match q.get("comments") {
+                Some(Value::Array(comments)) => comments
+                    .iter()
+                    .filter_map(|c| c.get("students")?.as_array())
+                    .flatten()
+                    .filter_map(|s| s.as_u64())
+                    .collect(),
+                _ => Vec::new(),
+            };
+            graded.sort_unstable();
+            graded.dedup();
+            q.insert("graded".to_string(), json!(graded));
+        }
+    }
+    asn
+}
+
+// Replaces the names a version 1 comment was given to with student ids.
+fn comment_names_to_ids<F>(comment: &mut Value, id_of: &F) -> Result<(), String>
+where
+    F: Fn(&Value) -> Result<Value, String>,
+{
+    let comment: &mut Map<String, Value> = comment.as_object_mut().ok_or("invalid comment data")?;
+    let ids = match comment.remove("names") {
+        Some(Value::Array(names)) => names.iter().map(id_of).collect::<Result<_, _>>()?,
+        _ => Vec::new(),
+    };
+    comment.insert("students".to_string(), Value::Array(ids));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::Question;
+    use crate::io::storage;
+
+    // An assignment as it was saved before the file layout was versioned.
+    fn v0_assignment() -> Value {
+        json!({
+            "title": "Assignment 5",
+            "course": "CS 1000",
+            "students": ["Issac Newton", "Albert Einstein"],
+            "questions": [
+                {
+                    "question": {"num": 1, "part": 1, "out_of": 5},
+                    "comments": [
+                        {
+                            "id": 0,
+                            "deduction": 3.0,
+                            "text": "Amateurish work",
+                            "names": ["Albert Einstein", "Issac Newton"]
+                        }
+                    ]
+                },
+                {
+                    "question": {"num": 1, "part": 2, "out_of": 5},
+                    "comments": []
+                }
+            ],
+            "next_id": 1
+        })
+    }
+
+    #[test]
+    fn it_treats_unversioned_files_as_version_0() {
+        let (version, asn) = split_version(v0_assignment()).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(asn, v0_assignment());
+    }
+
+    #[test]
+    fn it_reads_the_version_of_versioned_files() {
+        let saved = json!({"version": 1, "assignment": v0_assignment()});
+        let (version, asn) = split_version(saved).unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(asn, v0_assignment());
+    }
+
+    // A version 1 assignment with a comment in its history.
+    fn v1_assignment() -> Value {
+        let mut asn = v0_assignment();
+        asn["history"] = json!({
+            "undo": [
+                {
+                    "action": "StudentAdded",
+                    "question": {"num": 1, "part": 1, "out_of": 5},
+                    "id": 0,
+                    "student": "Issac Newton"
+                }
+            ],
+            "redo": [
+                {
+                    "action": "StudentRemoved",
+                    "question": {"num": 1, "part": 1, "out_of": 5},
+                    "student": "Albert Einstein",
+                    "comment": {
+                        "id": 0,
+                        "deduction": 3.0,
+                        "text": "Amateurish work",
+                        "names": ["Albert Einstein", "Issac Newton"]
+                    }
+                }
+            ]
+        });
+        asn
+    }
+
+    #[test]
+    fn it_upgrades_version_0_to_version_1_without_changes() {
+        assert_eq!(v0_to_v1(v0_assignment()), v0_assignment());
+    }
+
+    #[test]
+    fn it_upgrades_version_1_students_to_records_with_ids() {
+        let asn = v1_to_v2(v1_assignment()).unwrap();
+        assert_eq!(
+            asn["students"],
+            json!([
+                {"id": 0, "name": "Issac Newton"},
+                {"id": 1, "name": "Albert Einstein"}
+            ])
+        );
+        assert_eq!(asn["next_student_id"], json!(2));
+        assert_eq!(
+            asn["questions"][0]["comments"][0]["students"],
+            json!([1, 0])
+        );
+        assert_eq!(asn["history"]["undo"][0]["student"], json!(0));
+        assert_eq!(asn["history"]["redo"][0]["student"], json!(1));
+        assert_eq!(
+            asn["history"]["redo"][0]["comment"]["students"],
+            json!([1, 0])
+        );
+    }
+
+    #[test]
+    fn it_upgrades_version_2_to_graded_students_from_their_comments() {
+        let asn = v2_to_v3(v1_to_v2(v1_assignment()).unwrap());
+        assert_eq!(asn["questions"][0]["graded"], json!([0, 1]));
+        assert_eq!(asn["questions"][1]["graded"], json!([]));
+    }
+
+    #[test]
+    fn it_rejects_version_1_comments_for_unknown_students() {
+        let mut asn = v0_assignment();
+        asn["questions"][0]["comments"][0]["names"] = json!(["Marie Currie"]);
+        assert!(v1_to_v2(asn).is_err());
+    }
+
+    #[test]
+    fn it_loads_version_0_files_in_both_formats() {
+        let pickled = serde_pickle::ser::to_vec(&v0_assignment(), true).unwrap();
+        let json = serde_json::ser::to_vec(&v0_assignment()).unwrap();
+
+        for bytes in [pickled, json].iter() {
+            let asn = storage::from_bytes(bytes).unwrap();
+            let q = Question::new(1, 1, 5);
+            assert_eq!(asn.title, "Assignment 5".to_string());
+            assert_eq!(asn.get_student_at(0).name, "Issac Newton");
+            assert_eq!(asn.get_student_at(1).name, "Albert Einstein");
+            assert_eq!(asn.num_questions(), 2);
+            assert_eq!(asn.students_mark_for(0, &q), 2.0);
+            assert_eq!(asn.students_mark_for(1, &q), 2.0);
+        }
+    }
+
+    // Written by `AppFileOps::save_assignment` in the last release before
+    // files were versioned, for the same assignment as `v0_assignment`.
+    const V0_BASELINE_FILE: &[u8] = include_bytes!("testdata/v0_baseline.emark");
+
+    #[test]
+    fn it_upgrades_files_saved_before_versioning_to_the_current_version() {
+        let saved: Value = serde_pickle::de::from_slice(V0_BASELINE_FILE).unwrap();
+        let (version, _) = split_version(saved.clone()).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(CURRENT_VERSION, 3);
+        let upgraded = upgrade(saved).unwrap();
+        assert_eq!(upgraded["questions"][0]["graded"], json!([0, 1]));
+
+        let asn = storage::from_bytes(V0_BASELINE_FILE).unwrap();
+        let q1 = Question::new(1, 1, 5);
+        let q2 = Question::new(1, 2, 5);
+        assert_eq!(asn.title, "Assignment 5".to_string());
+        assert_eq!(asn.course, "CS 1000".to_string());
+        assert_eq!(asn.get_student_at(0).name, "Issac Newton");
+        assert_eq!(asn.get_student_at(1).name, "Albert Einstein");
+        assert_eq!(asn.students_mark_for(0, &q1), 2.0);
+        assert_eq!(asn.students_mark_for(1, &q1), 2.0);
+        assert!(asn.is_graded(0, &q1) && asn.is_graded(1, &q1));
+        assert!(!asn.is_graded(0, &q2));
+    }
+
+    #[test]
+    fn it_loads_version_1_files_with_history() {
+        let saved = json!({"version": 1, "assignment": v1_assignment()});
+        let mut asn = storage::from_bytes(&serde_json::to_vec(&saved).unwrap()).unwrap();
+        let q = Question::new(1, 1, 5);
+        assert!(asn.undo().is_some());
+        assert_eq!(asn.students_comments_for(0, &q).len(), 0);
+        assert_eq!(asn.students_comments_for(1, &q).len(), 1);
+    }
+
+    #[test]
+    fn it_rejects_files_from_a_newer_version() {
+        let saved = json!({"version": CURRENT_VERSION + 1, "assignment": {}});
+        assert!(upgrade(saved).is_err());
+    }
+}
@@ -1,11 +1,19 @@
 pub use self::app_file_ops::AppFileOps;
 pub use self::app_view::AppView;
+pub use self::error::FileError;
 pub use self::file_ops::FileOps;
-pub use self::view::View;
+pub use self::storage::StorageFormat;
+pub use self::view::{LoadChoice, View};
 mod app_file_ops;
 mod app_view;
+pub mod backup;
+mod error;
 mod file_ops;
 pub mod input;
+mod migrate;
+pub mod pdf;
+pub mod render;
+pub mod storage;
 mod view;
 
 #[cfg(test)]
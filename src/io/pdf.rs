@@ -0,0 +1,134 @@
+use crate::data::latex::LatexEngine;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+// The files pdflatex and xelatex leave beside the PDF.
+const AUX_EXTENSIONS: [&str; 4] = ["aux", "log", "out", "toc"];
+
+// The most errors kept in a summary.
+const MAX_ERRORS: usize = 3;
+
+/// Compiles a LaTeX file to a PDF in the same directory, then removes the
+/// auxiliary files the engine leaves behind. The log is kept if it fails.
+/// Returns a summary of the errors if the engine is not installed or the
+/// file did not compile.
+pub fn compile(engine: LatexEngine, tex_file: &str) -> Result<(), String> {
+    let path = Path::new(tex_file);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().to_string(),
+        _ => ".".to_string(),
+    };
+
+    let output = Command::new(engine.name())
+        .args(engine_args(engine, tex_file, &dir))
+        .output()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => format!("{} is not installed", engine.name()),
+            _ => format!("could not run {}: {}", engine.name(), e),
+        })?;
+
+    let ok = output.status.success();
+    for ext in AUX_EXTENSIONS.iter().filter(|ext| ok || **ext != "log") {
+        let _ = fs::remove_file(path.with_extension(ext));
+    }
+    if ok {
+        Ok(())
+    } else {
+        let mut log = String::from_utf8_lossy(&output.stdout).to_string();
+        log.push_str(&String::from_utf8_lossy(&output.stderr));
+        Err(error_summary(&log))
+    }
+}
+
+// The arguments that compile the file without stopping for input, putting
+// the output in the given directory.
+fn engine_args(engine: LatexEngine, tex_file: &str, dir: &str) -> Vec<String> {
+    let args = match engine {
+        LatexEngine::Pdflatex | LatexEngine::Xelatex => vec![
+            "-interaction=nonstopmode".to_string(),
+            "-halt-on-error".to_string(),
+            format!("-output-directory={}", dir),
+        ],
+        LatexEngine::Tectonic => vec!["--outdir".to_string(), dir.to_string()],
+    };
+    args.into_iter().chain(vec![tex_file.to_string()]).collect()
+}
+
+/// Picks the errors out of a LaTeX engine's output. TeX errors start with
+/// `!` and are followed by the line they happened on, and tectonic errors
+/// start with `error:`. If there are none, the end of the output is used.
+pub fn error_summary(log: &str) -> String {
+    let lines: Vec<&str> = log.lines().map(|l| l.trim_end()).collect();
+    let mut errors = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with('!') {
+            let at = lines[i + 1..].iter().take(5).find(|l| l.starts_with("l."));
+            errors.push(match at {
+                Some(at) => format!("{} ({})", line, at),
+                None => line.to_string(),
+            });
+        } else if line.starts_with("error:") {
+            errors.push(line.to_string());
+        }
+    }
+    if errors.is_empty() {
+        let last: Vec<&str> = lines
+            .iter()
+            .rev()
+            .filter(|l| !l.is_empty())
+            .take(2)
+            .cloned()
+            .collect();
+        errors = last.into_iter().rev().map(String::from).collect();
+    }
+    errors.truncate(MAX_ERRORS);
+    errors.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_runs_engines_without_stopping_for_input() {
+        assert_eq!(
+            engine_args(LatexEngine::Pdflatex, "dir/a.tex", "dir"),
+            vec![
+                "-interaction=nonstopmode",
+                "-halt-on-error",
+                "-output-directory=dir",
+                "dir/a.tex"
+            ]
+        );
+        assert_eq!(
+            engine_args(LatexEngine::Tectonic, "a.tex", "."),
+            vec!["--outdir", ".", "a.tex"]
+        );
+    }
+
+    #[test]
+    fn it_summarizes_tex_errors_with_their_lines() {
+        let log = "This is pdfTeX\n\
+                   ! Missing $ inserted.\n\
+                   <inserted text>\n\
+                   l.12 \\item[-1] use x_1\n\
+                   \n\
+                   ! Emergency stop.\n\
+                   No pages of output.\n";
+        assert_eq!(
+            error_summary(log),
+            "! Missing $ inserted. (l.12 \\item[-1] use x_1)\n! Emergency stop."
+        );
+    }
+
+    #[test]
+    fn it_summarizes_tectonic_errors_or_the_end_of_the_output() {
+        assert_eq!(
+            error_summary("note: running\nerror: a.tex:3: Undefined control sequence\n"),
+            "error: a.tex:3: Undefined control sequence"
+        );
+        assert_eq!(error_summary("one\ntwo\nthree\n\n"), "two\nthree");
+    }
+}
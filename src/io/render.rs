@@ -0,0 +1,263 @@
+use crate::data::template::Template;
+use crate::data::{html, latex, markdown, Assignment};
+use crate::io::{pdf, FileError};
+
+/// A format grade sheets can be exported in. The file ops load the format's
+/// template once per export, write whatever `render` returns for each
+/// student, then call `after_writing` on each sheet if the format has more to
+/// do to it.
+pub trait GradeSheetRenderer {
+    /// The name of the format, as typed on the command line.
+    fn name(&self) -> &'static str;
+
+    /// A short description of the format for showing to the user.
+    fn describe(&self) -> &'static str;
+
+    /// The extension of grade sheet files in the format.
+    fn extension(&self) -> &'static str;
+
+    /// Gets the template the assignment's grade sheets are laid out with,
+    /// along with a name for it to use in errors. Template files are read
+    /// with `load`.
+    fn template(
+        &self,
+        assignment: &Assignment,
+        load: &dyn Fn(&str) -> Result<Template, FileError>,
+    ) -> Result<(String, Template), FileError>;
+
+    /// Converts a student's marks and comments into a grade sheet laid out
+    /// with the template.
+    /// Returns an error if the template could not be filled in.
+    fn render(
+        &self,
+        assignment: &Assignment,
+        student: u64,
+        template: &Template,
+    ) -> Result<String, String>;
+
+    /// Describes what `after_writing` does to the assignment's grade sheets,
+    /// or `None` if there is nothing more to do, which is the default.
+    fn after_writing_step(&self, _assignment: &Assignment) -> Option<String> {
+        None
+    }
+
+    /// Runs on a grade sheet once it has been written to `filename`.
+    /// Returns a summary of what went wrong if it failed.
+    fn after_writing(&self, _assignment: &Assignment, _filename: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// The formats that come with easy-mark, in the order they are offered to
+/// the user. A new format only needs a `GradeSheetRenderer` and an entry
+/// here.
+pub fn built_in_renderers() -> Vec<Box<dyn GradeSheetRenderer>> {
+    vec![
+        Box::new(LatexRenderer),
+        Box::new(MarkdownRenderer),
+        Box::new(HtmlRenderer),
+    ]
+}
+
+/// Finds a built in format by its name, ignoring case.
+pub fn find_renderer(name: &str) -> Option<Box<dyn GradeSheetRenderer>> {
+    let name = name.to_lowercase();
+    built_in_renderers().into_iter().find(|r| r.name() == name)
+}
+
+/// Lays out grade sheets in LaTeX, with the assignment's own template if it
+/// has one, and compiles each one to PDF if the assignment has an engine set.
+pub struct LatexRenderer;
+
+impl GradeSheetRenderer for LatexRenderer {
+    fn name(&self) -> &'static str {
+        "latex"
+    }
+
+    fn describe(&self) -> &'static str {
+        "LaTeX"
+    }
+
+    fn extension(&self) -> &'static str {
+        "tex"
+    }
+
+    fn template(
+        &self,
+        assignment: &Assignment,
+        load: &dyn Fn(&str) -> Result<Template, FileError>,
+    ) -> Result<(String, Template), FileError> {
+        match assignment.latex_template() {
+            Some(path) => Ok((path.to_string(), load(path)?)),
+            None => default_template(self, latex::DEFAULT_TEMPLATE),
+        }
+    }
+
+    fn render(
+        &self,
+        assignment: &Assignment,
+        student: u64,
+        template: &Template,
+    ) -> Result<String, String> {
+        template.render(&latex::context(assignment, student))
+    }
+
+    fn after_writing_step(&self, assignment: &Assignment) -> Option<String> {
+        let engine = assignment.pdf_engine()?;
+        Some(format!("Compiling Grade Sheets With {}", engine.name()))
+    }
+
+    fn after_writing(&self, assignment: &Assignment, filename: &str) -> Result<(), String> {
+        match assignment.pdf_engine() {
+            Some(engine) => pdf::compile(engine, filename),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Lays out grade sheets in Markdown.
+pub struct MarkdownRenderer;
+
+impl GradeSheetRenderer for MarkdownRenderer {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Markdown"
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn template(
+        &self,
+        _assignment: &Assignment,
+        _load: &dyn Fn(&str) -> Result<Template, FileError>,
+    ) -> Result<(String, Template), FileError> {
+        default_template(self, markdown::DEFAULT_TEMPLATE)
+    }
+
+    fn render(
+        &self,
+        assignment: &Assignment,
+        student: u64,
+        template: &Template,
+    ) -> Result<String, String> {
+        template.render(&markdown::context(assignment, student))
+    }
+}
+
+/// Lays out grade sheets as self-contained HTML pages.
+pub struct HtmlRenderer;
+
+impl GradeSheetRenderer for HtmlRenderer {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn describe(&self) -> &'static str {
+        "HTML"
+    }
+
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn template(
+        &self,
+        _assignment: &Assignment,
+        _load: &dyn Fn(&str) -> Result<Template, FileError>,
+    ) -> Result<(String, Template), FileError> {
+        default_template(self, html::DEFAULT_TEMPLATE)
+    }
+
+    fn render(
+        &self,
+        assignment: &Assignment,
+        student: u64,
+        template: &Template,
+    ) -> Result<String, String> {
+        template.render(&html::context(assignment, student))
+    }
+}
+
+// Parses a format's built in template, along with a name for it to use in
+// errors.
+fn default_template(
+    renderer: &dyn GradeSheetRenderer,
+    text: &str,
+) -> Result<(String, Template), FileError> {
+    let path = format!("the default {} template", renderer.describe());
+    let template =
+        Template::parse(text).map_err(|e| FileError::InvalidTemplate(path.clone(), e))?;
+    Ok((path, template))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::latex::LatexEngine;
+    use crate::data::{Question, Student};
+
+    #[test]
+    fn it_finds_renderers_by_name_ignoring_case() {
+        assert_eq!(find_renderer("LaTeX").unwrap().extension(), "tex");
+        assert_eq!(find_renderer("markdown").unwrap().extension(), "md");
+        assert_eq!(find_renderer("HTML").unwrap().extension(), "html");
+        assert!(find_renderer("docx").is_none());
+    }
+
+    fn no_template_files(path: &str) -> Result<Template, FileError> {
+        panic!("tried to load {}", path)
+    }
+
+    // Renders the student's grade sheet with the format's template.
+    fn render(renderer: &dyn GradeSheetRenderer, asn: &Assignment, student: u64) -> String {
+        let (_, template) = renderer.template(asn, &no_template_files).unwrap();
+        renderer.render(asn, student, &template).unwrap()
+    }
+
+    #[test]
+    fn it_renders_with_each_formats_default_template() {
+        let q = Question::new(1, 1, 5);
+        let mut asn = Assignment::new("Lab 1".to_string(), "CS 1000".to_string());
+        asn.add_question(q.num, q.part, q.out_of);
+        let student = asn.add_student(Student::new("Ada Lovelace"));
+        asn.add_comment(student, &q, 1.0, "Use $x_1$".to_string());
+        asn.set_comment_raw_latex(&q, 0, true);
+
+        assert!(render(&LatexRenderer, &asn, student).contains("] Use $x_1$\n"));
+        assert!(render(&MarkdownRenderer, &asn, student).contains("- **-1** Use $x\\_1$\n"));
+        assert!(render(&HtmlRenderer, &asn, student).contains("</span> Use $x_1$</li>"));
+    }
+
+    #[test]
+    fn it_loads_the_assignments_latex_template_with_the_file_ops() {
+        let mut asn = Assignment::new("Lab 1".to_string(), "CS 1000".to_string());
+        asn.set_latex_template(Some("sheet.tex".to_string()));
+        let load = |path: &str| {
+            assert_eq!(path, "sheet.tex");
+            Ok(Template::parse("{{student}}").unwrap())
+        };
+
+        let (path, template) = LatexRenderer.template(&asn, &load).unwrap();
+        assert_eq!(path, "sheet.tex");
+        assert_eq!(template, Template::parse("{{student}}").unwrap());
+    }
+
+    #[test]
+    fn it_only_compiles_latex_grade_sheets_with_an_engine_set() {
+        let mut asn = Assignment::new("Lab 1".to_string(), "CS 1000".to_string());
+        assert_eq!(LatexRenderer.after_writing_step(&asn), None);
+        assert_eq!(MarkdownRenderer.after_writing_step(&asn), None);
+
+        asn.set_pdf_engine(Some(LatexEngine::Tectonic));
+        assert_eq!(
+            LatexRenderer.after_writing_step(&asn),
+            Some("Compiling Grade Sheets With tectonic".to_string())
+        );
+        assert_eq!(HtmlRenderer.after_writing_step(&asn), None);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::data::Assignment;
+use crate::io::migrate::{self, CURRENT_VERSION};
+use serde::Serialize;
+use serde_json::Value;
+
+/// The formats an assignment can be stored in on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageFormat {
+    /// The original binary serde-pickle format.
+    Pickle,
+    /// Pretty printed JSON that can be read, diffed, and fixed by hand.
+    Json,
+}
+
+impl StorageFormat {
+    /// The format to save a file in, chosen by its extension.
+    /// Files ending in `.json` are stored as JSON, everything else is pickled.
+    pub fn from_filename(filename: &str) -> StorageFormat {
+        if filename.ends_with(".json") {
+            StorageFormat::Json
+        } else {
+            StorageFormat::Pickle
+        }
+    }
+
+    /// Guesses the format of a saved file from its contents. JSON files
+    /// always start with an object, which a pickle never does.
+    pub fn detect(bytes: &[u8]) -> StorageFormat {
+        match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => StorageFormat::Json,
+            _ => StorageFormat::Pickle,
+        }
+    }
+
+    /// The file extension used for assignments saved in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StorageFormat::Pickle => ".emark",
+            StorageFormat::Json => ".emark.json",
+        }
+    }
+}
+
+// The layout of a saved file. The assignment is wrapped with the version of
+// the layout so that older files can be upgraded when they are loaded.
+#[derive(Serialize)]
+struct SavedAssignment<'a> {
+    version: u64,
+    assignment: &'a Assignment,
+}
+
+/// True if the file name looks like a saved assignment in any format.
+pub fn is_assignment_file(filename: &str) -> bool {
+    filename.ends_with(StorageFormat::Pickle.extension())
+        || filename.ends_with(StorageFormat::Json.extension())
+}
+
+/// The default file name for an assignment saved in the given format.
+pub fn assignment_filename(assignment: &Assignment, format: StorageFormat) -> String {
+    format!(
+        "{}_{}{}",
+        assignment.course,
+        assignment.title,
+        format.extension()
+    )
+    .replace(" ", "_")
+}
+
+/// Serializes an assignment in the given format along with the current
+/// file version. Pickles hold the same data as the JSON, since pickle has no
+/// way to store enums that can be read back generically for migration.
+pub fn to_bytes(assignment: &Assignment, format: StorageFormat) -> Result<Vec<u8>, String> {
+    let saved = SavedAssignment {
+        version: CURRENT_VERSION,
+        assignment,
+    };
+    match format {
+        StorageFormat::Pickle => {
+            let value = serde_json::to_value(&saved).map_err(|e| e.to_string())?;
+            serde_pickle::ser::to_vec(&value, true).map_err(|e| e.to_string())
+        }
+        StorageFormat::Json => {
+            let mut bytes = serde_json::ser::to_vec_pretty(&saved).map_err(|e| e.to_string())?;
+            bytes.push(b'\n');
+            Ok(bytes)
+        }
+    }
+}
+
+/// Deserializes an assignment saved in either format, upgrading files saved
+/// with an older layout to the current one.
+/// Returns the reason as an error if the bytes are not a valid assignment.
+pub fn from_bytes(bytes: &[u8]) -> Result<Assignment, String> {
+    let saved: Value = match StorageFormat::detect(bytes) {
+        StorageFormat::Pickle => serde_pickle::de::from_slice(bytes).map_err(|e| e.to_string())?,
+        StorageFormat::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string())?,
+    };
+    serde_json::from_value(migrate::upgrade(saved)?).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::{Question, Scaling, Student};
+
+    fn make_test_asn() -> Assignment {
+        let q1 = Question::new(1, 1, 5);
+        let q2 = Question::new(1, 2, 5);
+
+        let mut asn = Assignment::new("Assignment 5".to_string(), "CS 1000".to_string());
+        asn.add_question(q1.num, q1.part, q1.out_of);
+        asn.add_question(q2.num, q2.part, q2.out_of);
+        let newton = asn.add_student(Student::new("Issac Newton"));
+        let einstein = asn.add_student(Student::new("Albert Einstein"));
+
+        asn.add_comment(einstein, &q1, 3.0, "Amateurish work".to_string());
+        asn.add_to_comment(newton, &q1, 0);
+        asn.add_comment(newton, &q2, 1.5, "Try harder".to_string());
+        asn.set_scaling(vec![Scaling::ToMean(8.0), Scaling::OutOf(20)]);
+        asn
+    }
+
+    fn assert_same(a: &Assignment, b: &Assignment) {
+        assert_eq!(a.title, b.title);
+        assert_eq!(a.course, b.course);
+        assert_eq!(a.get_students(), b.get_students());
+        assert_eq!(a.get_questions(), b.get_questions());
+        assert_eq!(a.scaling(), b.scaling());
+        for s in a.get_students().iter() {
+            assert_eq!(a.students_total(s.id), b.students_total(s.id));
+        }
+    }
+
+    #[test]
+    fn it_picks_the_format_from_the_file_extension() {
+        assert_eq!(
+            StorageFormat::from_filename("CS_1000_A5.emark"),
+            StorageFormat::Pickle
+        );
+        assert_eq!(
+            StorageFormat::from_filename("CS_1000_A5.emark.json"),
+            StorageFormat::Json
+        );
+        assert!(is_assignment_file("CS_1000_A5.emark"));
+        assert!(is_assignment_file("CS_1000_A5.emark.json"));
+        assert!(!is_assignment_file("notes.json"));
+    }
+
+    #[test]
+    fn it_names_files_after_the_course_and_title() {
+        let asn = make_test_asn();
+        assert_eq!(
+            assignment_filename(&asn, StorageFormat::Pickle),
+            "CS_1000_Assignment_5.emark"
+        );
+        assert_eq!(
+            assignment_filename(&asn, StorageFormat::Json),
+            "CS_1000_Assignment_5.emark.json"
+        );
+    }
+
+    #[test]
+    fn it_round_trips_both_formats_and_detects_them_on_load() {
+        let asn = make_test_asn();
+        for format in [StorageFormat::Pickle, StorageFormat::Json].iter() {
+            let bytes = to_bytes(&asn, *format).unwrap();
+            assert_eq!(StorageFormat::detect(&bytes), *format);
+            let mut loaded = from_bytes(&bytes).unwrap();
+            assert_same(&asn, &loaded);
+            // The undo history is saved with the assignment.
+            assert!(loaded.undo().is_some());
+        }
+    }
+
+    #[test]
+    fn it_writes_json_with_a_stable_layout() {
+        let asn = make_test_asn();
+        let first = to_bytes(&asn, StorageFormat::Json).unwrap();
+        let second = to_bytes(&from_bytes(&first).unwrap(), StorageFormat::Json).unwrap();
+        assert_eq!(first, second);
+
+        let text = String::from_utf8(first).unwrap();
+        assert!(text.starts_with(&format!(
+            "{{\n  \"version\": {},\n  \"assignment\": {{\n",
+            CURRENT_VERSION
+        )));
+        assert!(text.contains("\"title\": \"Assignment 5\",\n    \"course\": \"CS 1000\","));
+        assert!(text.contains("\"students\": [\n"));
+    }
+
+    #[test]
+    fn it_gives_an_error_for_files_that_are_not_assignments() {
+        assert!(from_bytes(b"").is_err());
+        assert!(from_bytes(b"not an assignment").is_err());
+        assert!(from_bytes(b"{\"title\": 5}").is_err());
+    }
+}
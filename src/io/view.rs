@@ -1,27 +1,70 @@
-use crate::data::{Assignment, Question};
+use crate::data::late::LatePolicy;
+use crate::data::lms::Lms;
+use crate::data::roster::RosterColumns;
+use crate::data::usage::UsageOrder;
+use crate::data::{Assignment, Question, Scaling, Student};
+use crate::io::render::GradeSheetRenderer;
+use crate::io::{FileError, StorageFormat};
+use chrono::NaiveDateTime;
 
 #[cfg(test)]
 use mockall::automock;
 
+/// A file chosen from the Load Assignment menu.
+#[derive(Debug, PartialEq)]
+pub enum LoadChoice {
+    /// A saved assignment to open.
+    File(String),
+    /// A backup to restore over its assignment file before opening it.
+    Backup(String),
+}
+
 #[cfg_attr(test, automock)]
 pub trait View {
     fn clear_screen();
-    fn show_menu(header: &str, menu: &Vec<String>) -> u32;
-    fn new_student(assignment: &Assignment) -> String;
+    fn show_error(error: &FileError);
+    fn show_message(message: &str);
+    fn show_menu(header: &str, menu: &[String]) -> u32;
+    fn new_student(assignment: &Assignment) -> Student;
+    fn rename_student(student: &Student) -> Option<String>;
+    fn choose_student(students: &[Student], header: &str) -> Option<u64>;
+    fn go_to_student(assignment: &Assignment) -> Option<u64>;
+    fn go_to_question(assignment: &Assignment) -> Option<Question>;
+    fn choose_roster() -> Option<(String, RosterColumns)>;
     fn create_assignment() -> Assignment;
-    fn load_assignment() -> Option<Assignment>;
+    fn choose_storage_format() -> StorageFormat;
+    fn choose_lms() -> Option<Lms>;
+    fn choose_usage_order() -> Option<UsageOrder>;
+    fn choose_renderer() -> Option<Box<dyn GradeSheetRenderer>>;
+    fn choose_assignment_file() -> Option<LoadChoice>;
+    fn choose_backup(backups: Vec<String>) -> Option<String>;
     fn new_comment() -> Option<(f32, String)>;
     fn add_existing_comment(
         assignment: &Assignment,
-        student: &str,
+        student: u64,
         question: &Question,
     ) -> Option<u64>;
     fn edit_comment(
         assignment: &Assignment,
-        student: &str,
+        student: u64,
         question: &Question,
     ) -> Option<(f32, String, u64)>;
-    fn remove_comment(assignment: &Assignment, student: &str, question: &Question) -> Option<u64>;
-    fn show_grade_sheet(assignment: &Assignment, student: &str);
-    fn show_question_info(assignment: &Assignment, student: &str, question: &Question);
+    fn remove_comment(assignment: &Assignment, student: u64, question: &Question) -> Option<u64>;
+    fn show_structure(assignment: &Assignment);
+    fn new_question_part(assignment: &Assignment) -> Option<Question>;
+    fn choose_question(assignment: &Assignment, header: &str) -> Option<Question>;
+    fn choose_comment(assignment: &Assignment, question: &Question, header: &str) -> Option<u64>;
+    fn new_out_of(question: &Question) -> Option<u32>;
+    fn renumber_question(assignment: &Assignment, question: &Question) -> Option<(u32, u32)>;
+    fn new_scaling(assignment: &Assignment) -> Option<Scaling>;
+    fn choose_date_time(header: &str, current: Option<NaiveDateTime>) -> Option<NaiveDateTime>;
+    fn new_late_policy(current: LatePolicy) -> Option<LatePolicy>;
+    fn choose_template(current: &str) -> Option<String>;
+    fn confirm(message: &str) -> bool;
+    fn show_grade_sheet(assignment: &Assignment, student: u64);
+    fn show_question_info(assignment: &Assignment, student: u64, question: &Question);
+    fn show_comment_bank(assignment: &Assignment, student: u64, question: &Question);
+    fn show_statistics(assignment: &Assignment);
+    fn show_scaling_preview(assignment: &Assignment, steps: &[Scaling]);
+    fn show_comment_usage(assignment: &Assignment, order: UsageOrder);
 }
@@ -1,12 +1,20 @@
 mod app;
+mod cli;
 mod data;
 mod io;
 
 use crate::app::App;
 use crate::io::AppFileOps;
 use crate::io::AppView;
+use std::env;
+use std::process;
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        process::exit(cli::run::<AppFileOps>(&args));
+    }
+
     let mut app = App::<AppView, AppFileOps>::new();
     app.run();
 }